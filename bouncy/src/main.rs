@@ -57,7 +57,12 @@ fn main() -> anyhow::Result<()> {
         GamePhase::GameOver,
     ))
     .add_plugins(RandomPlugin)
-    .add_plugins(AssetManager::new().add_image("green_ball", "green_ball.png")?)
+    .add_plugins(
+        AssetManager::new()
+            .add_image("main_menu", "main_menu.png")?
+            .add_image("game_over", "game_over.png")?
+            .add_image("green_ball", "green_ball.png")?,
+    )
     .run();
 
     Ok(())
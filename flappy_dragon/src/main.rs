@@ -57,6 +57,8 @@ fn main() -> anyhow::Result<()> {
     ))
     .add_plugins(
         AssetManager::new()
+            .add_image("main_menu", "main_menu.png")?
+            .add_image("game_over", "game_over.png")?
             .add_image("dragon", "flappy_dragon.png")?
             .add_image("wall", "wall.png")?
             .add_sound("flap", "dragonflap.ogg")?
@@ -65,42 +67,10 @@ fn main() -> anyhow::Result<()> {
             .add_image("bg_static", "rocky-far-mountains.png")?
             .add_image("bg_far", "rocky-nowater-far.png")?
             .add_image("bg_mid", "rocky-nowater-mid.png")?
-            .add_image("bg_close", "rocky-nowater-close.png")?,
-    )
-    .insert_resource(
-        Animations::new()
-            .with_animation(
-                "Straight and Level",
-                PerFrameAnimation::new(vec![
-                    AnimationFrame::new(2, 500, vec![AnimationOption::NextFrame]),
-                    AnimationFrame::new(3, 500, vec![AnimationOption::GoToFrame(0)]),
-                ]),
-            )
-            .with_animation(
-                "Flapping",
-                PerFrameAnimation::new(vec![
-                    AnimationFrame::new(
-                        0,
-                        66,
-                        vec![
-                            AnimationOption::NextFrame,
-                            AnimationOption::PlaySound("flap".to_string()),
-                        ],
-                    ),
-                    AnimationFrame::new(1, 66, vec![AnimationOption::NextFrame]),
-                    AnimationFrame::new(2, 66, vec![AnimationOption::NextFrame]),
-                    AnimationFrame::new(3, 66, vec![AnimationOption::NextFrame]),
-                    AnimationFrame::new(2, 66, vec![AnimationOption::NextFrame]),
-                    AnimationFrame::new(
-                        1,
-                        66,
-                        vec![AnimationOption::SwitchToAnimation(
-                            "Straight and Level".to_string(),
-                        )],
-                    ),
-                ]),
-            ),
+            .add_image("bg_close", "rocky-nowater-close.png")?
+            .add_animations("flappy_animations", "flappy.anim.ron")?,
     )
+    .add_plugins(GameAudio::new().with_max_instances("flap", 1))
     .add_event::<OnCollision<Flappy, Obstacle>>()
     .run();
 
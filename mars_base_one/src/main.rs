@@ -115,19 +115,18 @@ impl World {
             }
         }
 
-        let (mesh, tile_positions, spawn_positions) = result.build_mesh();
+        let (mesh, tile_positions) = result.build_mesh();
         result.mesh = Some(mesh);
         result.tile_positons = tile_positions;
-        result.spawn_positions = spawn_positions;
+        result.spawn_positions = result.find_spawn_positions(rng);
 
         result
     }
 
-    fn build_mesh(&self) -> (Mesh, Vec<(f32, f32)>, Vec<(f32, f32)>) {
+    fn build_mesh(&self) -> (Mesh, Vec<(f32, f32)>) {
         let mut position = vec![];
         let mut uv = vec![];
         let mut tile_positions = vec![];
-        let mut possible_miner_positions = vec![];
         let x_offset = (self.width as f32 / 2.0) * 24.0;
         let y_offset = (self.height as f32) * 24.0;
         for y in 0..self.height {
@@ -170,15 +169,6 @@ impl World {
                     if needs_physics {
                         tile_positions.push((left + 12.0, top + 12.0));
                     }
-                } else {
-                    if x > 1
-                        && x < self.width - 3
-                        && y > 1
-                        && y < self.height - 3
-                        && self.solid[self.mapidx(x, y - 1)]
-                    {
-                        possible_miner_positions.push((left + 12.0, top + 12.0));
-                    }
                 }
             }
         }
@@ -191,10 +181,39 @@ impl World {
             .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, position)
             .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, uv),
             tile_positions,
-            possible_miner_positions,
         )
     }
 
+    fn find_spawn_positions(&self, rng: &mut RandomNumberGenerator) -> Vec<(f32, f32)> {
+        // Pickups stand on open tiles with a floor beneath them, so they
+        // never float. Floors are too thin to scatter points over, so pick
+        // from the floor tiles themselves.
+        let floor_tiles: Vec<Vec2> = (2..self.height - 2)
+            .flat_map(|y| (2..self.width - 2).map(move |x| (x, y)))
+            .filter(|&(x, y)| !self.solid[self.mapidx(x, y)] && self.solid[self.mapidx(x, y - 1)])
+            .map(|(x, y)| Vec2::new(x as f32, y as f32))
+            .collect();
+        let caves = Rect2D::new(Vec2::ZERO, Vec2::new(self.width as f32, self.height as f32));
+        let points = PoissonDiscSampler::new(caves, 8.0)
+            .with_max_points(60)
+            .sample_from(rng, &floor_tiles);
+        if points.len() < 60 {
+            warn!("Only found room for {} of 60 pickups", points.len());
+        }
+
+        let x_offset = (self.width as f32 / 2.0) * 24.0;
+        let y_offset = (self.height as f32) * 24.0;
+        points
+            .iter()
+            .map(|point| {
+                (
+                    point.x * 24.0 - x_offset + 12.0,
+                    point.y * 24.0 - y_offset + 12.0,
+                )
+            })
+            .collect()
+    }
+
     fn spawn(
         &self,
         assets: &AssetStore,
@@ -369,23 +388,18 @@ fn main() -> anyhow::Result<()> {
 
         ..default()
     }))
-    .add_plugins(RandomPlugin)
+    .add_plugins(
+        RandomPlugin::new()
+            .with_seed_from_env("MARS_SEED")
+            .with_seed_from_args("--seed"),
+    )
     .add_plugins(GameStatePlugin::new(
         GamePhase::MainMenu,
         GamePhase::WorldBuilding,
         GamePhase::GameOver,
     ))
-    .add_plugins(
-        AssetManager::new()
-            .add_image("ship", "ship.png")?
-            .add_image("ground", "ground.png")?
-            .add_image("backdrop", "backing.png")?
-            .add_image("mothership", "mothership.png")?
-            .add_image("particle", "particle.png")?
-            .add_image("spaceman", "spaceman.png")?
-            .add_image("fuel", "fuel.png")?
-            .add_image("battery", "battery.png")?,
-    )
+    .add_plugins(AssetManager::from_manifest("assets/manifest.ron")?)
+    .add_plugins(GameAudio::new().with_max_instances("crash", 1))
     .add_plugins(FrameTimeDiagnosticsPlugin { ..default() })
     .insert_resource(Animations::new())
     .add_event::<OnCollision<Player, Ground>>()
@@ -401,16 +415,13 @@ fn main() -> anyhow::Result<()> {
 static WORLD_READY: AtomicBool = AtomicBool::new(false);
 static NEW_WORLD: Mutex<Option<World>> = Mutex::new(None);
 
-fn spawn_builder() {
+fn spawn_builder(mut streams: ResMut<RandomStreams>) {
     use std::sync::atomic::Ordering;
     WORLD_READY.store(false, Ordering::Relaxed);
 
-    std::thread::spawn(|| {
-        let mut rng = my_library::RandomNumberGenerator::new();
-        let mut world = World::new(200, 200, &mut rng);
-
-        use my_library::rand::seq::SliceRandom;
-        world.spawn_positions.shuffle(&mut rng.rng);
+    let mut rng = streams.fork("worldgen");
+    std::thread::spawn(move || {
+        let world = World::new(200, 200, &mut rng);
 
         let mut lock = NEW_WORLD.lock().unwrap();
         *lock = Some(world);
@@ -446,7 +457,8 @@ fn setup(
         .spawn(cb)
         .insert(projection)
         .insert(GameElement)
-        .insert(MyCamera);
+        .insert(MyCamera)
+        .insert(SoundListener);
 
     spawn_image!(
         assets,
@@ -595,6 +607,7 @@ fn camera_follow(
     camera.translation = Vec3::new(player.translation.x, player.translation.y, 10.0);
 }
 
+#[allow(clippy::too_many_arguments)]
 fn bounce(
     mut collisions: EventReader<OnCollision<Player, Ground>>,
     mut player_query: Query<(&PhysicsPosition, &mut Player)>,
@@ -602,6 +615,9 @@ fn bounce(
     mut impulses: EventWriter<Impulse>,
     mut particles: EventWriter<SpawnParticle>,
     mut state: ResMut<NextState<GamePhase>>,
+    assets: Res<AssetStore>,
+    loaded_assets: Res<LoadedAssets>,
+    mut commands: Commands,
 ) {
     let mut bounce = Vec2::default();
     let mut entity = None;
@@ -636,6 +652,7 @@ fn bounce(
             &mut particles,
             3.0,
         );
+        assets.play_at("crash", player_pos.end_frame, &mut commands, &loaded_assets);
 
         player.shields -= 1;
         if player.shields <= 0 {
@@ -680,7 +697,7 @@ fn spawn_particle_system(
     loaded_assets: Res<LoadedAssets>,
 ) {
     for particle in reader.read() {
-        let mut sprite = Sprite::from_image(assets.get_handle("particle", &loaded_assets).unwrap());
+        let mut sprite = assets.get_sprite("particle", &loaded_assets).unwrap();
         sprite.color = particle.color.into();
         commands
             .spawn(sprite)
@@ -751,7 +768,7 @@ fn particle_burst(
 }
 
 fn miner_beacon(
-    mut rng: ResMut<RandomNumberGenerator>,
+    mut rng: Random,
     miners: Query<&Transform, With<Miner>>,
     mut spawn: EventWriter<SpawnParticle>,
 ) {
@@ -1,14 +1,16 @@
 //! Roll 3d6 repeatedly and graph the resulting distribution.
-use my_library::RandomNumberGenerator;
+use my_library::{DiceRoll, RandomNumberGenerator};
 
 fn main() {
     // Create a random number generator
     let mut rng = RandomNumberGenerator::new();
     // Store results (minus 3)
     let mut results = vec![0; 16];
+    // Parse the dice expression once, and reuse it for every roll
+    let three_d_six = DiceRoll::parse("3d6").unwrap();
     // Roll 1,000 sets of 3d6 and increment results to map distribution
     for _ in 0..1000 {
-        let roll = rng.range(1..=6) + rng.range(1..=6) + rng.range(1..=6);
+        let roll = rng.roll(three_d_six) as usize;
         results[roll - 3] += 1;
     }
     // Print results
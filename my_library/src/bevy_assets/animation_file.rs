@@ -0,0 +1,248 @@
+use bevy::{
+    asset::{AssetLoader, LoadContext, io::Reader},
+    platform::collections::HashMap,
+    prelude::*,
+};
+use serde::Deserialize;
+
+use crate::{Animations, PerFrameAnimation};
+
+/// A file of named animations, in RON (`.anim.ron`) or JSON
+/// (`.anim.json`) format. Once loaded, its animations are added to the
+/// [`Animations`] resource, replacing any with the same name. They are
+/// removed again when the file is unloaded; if another file also has an
+/// animation with that name, its version is used instead.
+///
+/// ```ron
+/// {
+///     "Straight and Level": (frames: [
+///         (sprite_index: 2, delay_ms: 500, actions: [NextFrame]),
+///         (sprite_index: 3, delay_ms: 500, actions: [GoToFrame(0)]),
+///     ]),
+/// }
+/// ```
+#[derive(Asset, TypePath, Deserialize, Debug)]
+#[serde(transparent)]
+pub struct AnimationSet(HashMap<String, PerFrameAnimation>);
+
+#[derive(Default)]
+pub(crate) struct AnimationSetLoader;
+
+impl AssetLoader for AnimationSetLoader {
+    type Asset = AnimationSet;
+    type Settings = ();
+    type Error = anyhow::Error;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<AnimationSet, anyhow::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        parse_animations(&load_context.path().to_string_lossy(), &bytes)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["anim.ron", "anim.json"]
+    }
+}
+
+fn parse_animations(path: &str, bytes: &[u8]) -> anyhow::Result<AnimationSet> {
+    let animations = if path.ends_with(".json") {
+        serde_json::from_slice(bytes)?
+    } else {
+        ron::de::from_bytes(bytes)?
+    };
+    Ok(animations)
+}
+
+/// Tracks which files provide each animation, so that unloading or
+/// reloading one file doesn't remove animations another still provides.
+#[derive(Default)]
+pub(crate) struct AnimationSources {
+    /// The animations in each file.
+    names: HashMap<AssetId<AnimationSet>, Vec<String>>,
+    /// The files with each animation, most recently loaded last.
+    files: HashMap<String, Vec<AssetId<AnimationSet>>>,
+}
+
+impl AnimationSources {
+    fn add(&mut self, id: AssetId<AnimationSet>, set: &AnimationSet, animations: &mut Animations) {
+        for (name, animation) in set.0.iter() {
+            animations.insert(name.clone(), animation.clone());
+            self.files.entry(name.clone()).or_default().push(id);
+        }
+        self.names.insert(id, set.0.keys().cloned().collect());
+    }
+
+    /// Removes a file's animations, falling back to another file's version
+    /// of each one where there is one.
+    fn forget(
+        &mut self,
+        id: AssetId<AnimationSet>,
+        sets: &Assets<AnimationSet>,
+        animations: &mut Animations,
+    ) {
+        for name in self.names.remove(&id).into_iter().flatten() {
+            let Some(files) = self.files.get_mut(&name) else {
+                continue;
+            };
+            files.retain(|file| *file != id);
+            match files.last().and_then(|file| sets.get(*file)?.0.get(&name)) {
+                Some(animation) => animations.insert(name, animation.clone()),
+                None => {
+                    self.files.remove(&name);
+                    animations.remove(&name);
+                }
+            }
+        }
+    }
+}
+
+/// Copies animations into the [`Animations`] resource as their files load,
+/// and again whenever a file is reloaded. Animations removed from a file,
+/// or belonging to a file that is unloaded, are removed from the resource.
+pub(crate) fn sync_animations(
+    mut events: EventReader<AssetEvent<AnimationSet>>,
+    sets: Res<Assets<AnimationSet>>,
+    mut animations: ResMut<Animations>,
+    mut sources: Local<AnimationSources>,
+) {
+    for event in events.read() {
+        match event {
+            AssetEvent::Added { id } | AssetEvent::Modified { id } => {
+                sources.forget(*id, &sets, &mut animations);
+                if let Some(set) = sets.get(*id) {
+                    sources.add(*id, set, &mut animations);
+                }
+            }
+            AssetEvent::Removed { id } => {
+                sources.forget(*id, &sets, &mut animations);
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AnimationOption;
+
+    const FLAPPY: &str = r#"{
+        "Straight and Level": (frames: [
+            (sprite_index: 2, delay_ms: 500, actions: [NextFrame]),
+            (sprite_index: 3, delay_ms: 500, actions: [GoToFrame(0)]),
+        ]),
+        "Flapping": (frames: [
+            (sprite_index: 0, delay_ms: 66, actions: [NextFrame, PlaySound("flap")]),
+            (sprite_index: 1, delay_ms: 66, actions: [SwitchToAnimation("Straight and Level")]),
+        ]),
+    }"#;
+
+    #[test]
+    fn test_parse_ron_and_json() {
+        let set = parse_animations("flappy.anim.ron", FLAPPY.as_bytes()).unwrap();
+        assert_eq!(set.0.len(), 2);
+        let flapping = &set.0["Flapping"];
+        assert_eq!(flapping.frames.len(), 2);
+        assert!(matches!(
+            &flapping.frames[0].action[1],
+            AnimationOption::PlaySound(tag) if tag == "flap"
+        ));
+
+        let json = r#"{ "Idle": { "frames": [
+            { "sprite_index": 0, "delay_ms": 100 }
+        ] } }"#;
+        let set = parse_animations("hero.anim.json", json.as_bytes()).unwrap();
+        assert!(set.0["Idle"].frames[0].action.is_empty());
+    }
+
+    #[test]
+    fn test_loaded_animations_are_synced() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+            .init_asset::<AnimationSet>()
+            .init_resource::<Animations>()
+            .add_systems(Update, sync_animations);
+
+        let set = parse_animations("flappy.anim.ron", FLAPPY.as_bytes()).unwrap();
+        let handle = app
+            .world_mut()
+            .resource_mut::<Assets<AnimationSet>>()
+            .add(set);
+        app.update();
+        app.update();
+        assert!(
+            app.world()
+                .resource::<Animations>()
+                .get("Flapping")
+                .is_some()
+        );
+
+        // Reloading a file replaces its animations.
+        let reloaded = r#"{ "Gliding": (frames: [(sprite_index: 2, delay_ms: 100)]) }"#;
+        *app.world_mut()
+            .resource_mut::<Assets<AnimationSet>>()
+            .get_mut(&handle)
+            .unwrap() = parse_animations("flappy.anim.ron", reloaded.as_bytes()).unwrap();
+        app.update();
+        app.update();
+        let animations = app.world().resource::<Animations>();
+        assert!(animations.get("Flapping").is_none());
+        assert!(animations.get("Gliding").is_some());
+    }
+
+    #[test]
+    fn test_files_sharing_an_animation() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+            .init_asset::<AnimationSet>()
+            .init_resource::<Animations>()
+            .add_systems(Update, sync_animations);
+        let delay = |app: &App, name: &str| {
+            app.world()
+                .resource::<Animations>()
+                .get(name)
+                .map(|animation| animation.frames[0].delay.as_millis())
+        };
+
+        let add = |app: &mut App, ron: &str| {
+            let set = parse_animations("shared.anim.ron", ron.as_bytes()).unwrap();
+            let handle = app
+                .world_mut()
+                .resource_mut::<Assets<AnimationSet>>()
+                .add(set);
+            app.update();
+            app.update();
+            handle
+        };
+        let hero = add(
+            &mut app,
+            r#"{ "Idle": (frames: [(sprite_index: 0, delay_ms: 100)]) }"#,
+        );
+        let level = add(
+            &mut app,
+            r#"{
+                "Idle": (frames: [(sprite_index: 0, delay_ms: 200)]),
+                "Torch": (frames: [(sprite_index: 0, delay_ms: 50)]),
+            }"#,
+        );
+        assert_eq!(delay(&app, "Idle"), Some(200));
+
+        // Unloading the level falls back to the hero's "Idle", and removes
+        // the level's own animations.
+        drop(level);
+        app.update();
+        app.update();
+        assert_eq!(delay(&app, "Idle"), Some(100));
+        assert_eq!(delay(&app, "Torch"), None);
+
+        drop(hero);
+        app.update();
+        app.update();
+        assert_eq!(delay(&app, "Idle"), None);
+    }
+}
@@ -0,0 +1,144 @@
+use bevy::{platform::collections::HashMap, prelude::*};
+
+use super::asset_manager::AssetType;
+use crate::AssetStore;
+
+/// `AssetGroups` tracks the asset groups declared with
+/// [`AssetManager::group`](crate::AssetManager::group). A group's assets
+/// are loaded when something first acquires it, and unloaded once
+/// everything that acquired it has released it.
+///
+/// `GameStatePlugin::with_asset_group` acquires and releases groups as
+/// the game changes state; games can also hold groups themselves, for
+/// example to keep a level loaded while a cut-scene plays.
+#[derive(Resource)]
+pub struct AssetGroups {
+    definitions: HashMap<String, Vec<(String, String, AssetType)>>,
+    holders: HashMap<String, usize>,
+}
+
+impl AssetGroups {
+    pub(crate) fn new(definitions: HashMap<String, Vec<(String, String, AssetType)>>) -> Self {
+        Self {
+            definitions,
+            holders: HashMap::new(),
+        }
+    }
+
+    /// Returns `true` if a group with this name was declared.
+    pub fn contains(&self, group: &str) -> bool {
+        self.definitions.contains_key(group)
+    }
+
+    /// Returns how many times a group is currently held.
+    pub fn holders(&self, group: &str) -> usize {
+        self.holders.get(group).copied().unwrap_or(0)
+    }
+
+    /// Holds a group, starting to load its assets if it wasn't already
+    /// held.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no group with this name was declared.
+    pub fn acquire(&mut self, group: &str, store: &mut AssetStore) {
+        let Some(assets) = self.definitions.get(group) else {
+            panic!("No asset group named '{group}' was added to the AssetManager");
+        };
+        let holders = self.holders.entry(group.to_string()).or_default();
+        if *holders == 0 {
+            for (tag, filename, asset_type) in assets {
+                store.load(tag, filename, asset_type);
+            }
+        }
+        *holders += 1;
+    }
+
+    /// Releases a group, unloading its assets if nothing else holds it.
+    pub fn release(&mut self, group: &str, store: &mut AssetStore) {
+        let Some(holders) = self.holders.get_mut(group).filter(|holders| **holders > 0) else {
+            warn!("Asset group '{group}' was released more often than it was acquired");
+            return;
+        };
+        *holders -= 1;
+        if *holders == 0 {
+            for (tag, _, asset_type) in self.definitions.get(group).into_iter().flatten() {
+                store.unload(tag, asset_type);
+            }
+        }
+    }
+
+    /// The `AssetStore` index tags of a group's assets.
+    pub(crate) fn index_tags(&self, group: &str) -> Vec<String> {
+        self.definitions
+            .get(group)
+            .into_iter()
+            .flatten()
+            .filter_map(|(tag, _, asset_type)| match asset_type {
+                AssetType::SpriteSheet(_) => Some(format!("{tag}_base")),
+                AssetType::PackedAtlas(_) => None,
+                _ => Some(tag.clone()),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SpriteSheetLayout;
+
+    #[test]
+    fn test_groups_are_reference_counted() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()));
+        let mut store = AssetStore::new(app.world().resource::<AssetServer>().clone());
+        let mut definitions = HashMap::new();
+        definitions.insert(
+            "level2".to_string(),
+            vec![
+                ("boss".to_string(), "boss.png".to_string(), AssetType::Image),
+                (
+                    "bats".to_string(),
+                    "bats.png".to_string(),
+                    AssetType::SpriteSheet(SpriteSheetLayout::Grid {
+                        title_size: Vec2::new(16.0, 16.0),
+                        sprites_x: 4,
+                        sprites_y: 1,
+                        padding: Vec2::ZERO,
+                        offset: Vec2::ZERO,
+                    }),
+                ),
+            ],
+        );
+        let mut groups = AssetGroups::new(definitions);
+
+        groups.acquire("level2", &mut store);
+        groups.acquire("level2", &mut store);
+        assert_eq!(groups.holders("level2"), 2);
+        assert_eq!(groups.index_tags("level2"), vec!["boss", "bats_base"]);
+        assert!(store.asset_index.contains_key("boss"));
+        assert_eq!(store.atlases_to_build.len(), 1);
+
+        groups.release("level2", &mut store);
+        assert!(store.asset_index.contains_key("bats_base"));
+
+        groups.release("level2", &mut store);
+        assert_eq!(groups.holders("level2"), 0);
+        assert!(store.asset_index.is_empty());
+        assert!(store.atlases_to_build.is_empty());
+
+        // Releasing an unheld group is harmless.
+        groups.release("level2", &mut store);
+        assert_eq!(groups.holders("level2"), 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_unknown_group() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()));
+        let mut store = AssetStore::new(app.world().resource::<AssetServer>().clone());
+        AssetGroups::new(HashMap::new()).acquire("level2", &mut store);
+    }
+}
@@ -1,38 +1,104 @@
-use bevy::prelude::*;
+use bevy::{platform::collections::HashMap, prelude::*};
 
-use crate::{AssetStore, FutureAtlas};
+use crate::{AssetGroups, AssetReloaded, AssetStore, SpriteSheetLayout};
 
 #[derive(Clone)]
 pub enum AssetType {
     Image,
     Sound,
-    SpriteSheet {
-        title_size: Vec2,
-        sprites_x: usize,
-        sprites_y: usize,
-    },
+    Font,
+    SpriteSheet(SpriteSheetLayout),
+    PackedAtlas(Vec<String>),
+    #[cfg(feature = "serde")]
+    Animations,
 }
 
 #[derive(Resource, Clone)]
 pub struct AssetManager {
-    asset_list: Vec<(String, String, AssetType)>,
+    pub(crate) asset_list: Vec<(String, String, AssetType)>,
+    pub(crate) groups: HashMap<String, Vec<(String, String, AssetType)>>,
+    current_group: Option<String>,
+    hot_reload: bool,
+    file_path: String,
 }
 
 impl AssetManager {
     pub fn new() -> Self {
         Self {
-            asset_list: vec![
-                (
-                    "main_menu".to_string(),
-                    "main_menu.png".to_string(),
-                    AssetType::Image,
-                ),
-                (
-                    "game_over".to_string(),
-                    "game_over.png".to_string(),
-                    AssetType::Image,
-                ),
-            ],
+            asset_list: vec![],
+            groups: HashMap::new(),
+            current_group: None,
+            hot_reload: false,
+            file_path: AssetPlugin::default().file_path,
+        }
+    }
+
+    /// Development mode: reloads assets when their files change on disk,
+    /// sending an [`AssetReloaded`] event for each one. Sprite sheets whose
+    /// image changes size gain or lose rows and columns of sprites, sprite
+    /// atlases pick up edits to their JSON file, and edited animation files
+    /// update the [`Animations`](crate::Animations) resource.
+    ///
+    /// **This does nothing on its own.** Files are only watched when
+    /// my_library's `hot_reload` feature is enabled, which turns on Bevy's
+    /// `file_watcher` for every app built with it. This method only adds
+    /// the systems that handle reloaded files; without the feature, it just
+    /// logs a warning.
+    pub fn with_hot_reload(mut self) -> Self {
+        self.hot_reload = true;
+        self
+    }
+
+    /// Assets added after calling `group` belong to the named group, and
+    /// are only loaded while the group is held (see
+    /// `GameStatePlugin::with_asset_group` and [`AssetGroups`]). Assets
+    /// added before the first call are loaded when the game starts.
+    pub fn group<S: ToString>(mut self, name: S) -> Self {
+        let name = name.to_string();
+        self.groups.entry(name.clone()).or_default();
+        self.current_group = Some(name);
+        self
+    }
+
+    /// Creates an `AssetManager` from a manifest file, in RON (`.ron`) or
+    /// JSON (`.json`) format, listing `images`, `sounds`, `fonts`,
+    /// `sprite_sheets` (with optional `padding` and `offset`),
+    /// `sprite_atlases`, `packed_atlases` and `animations`. Every file is
+    /// checked, and all of the missing files (and sprite atlas frame files
+    /// that can't be parsed) are reported in a single error. Requires the
+    /// `serde` feature.
+    ///
+    /// ```ron
+    /// (
+    ///     images: [(tag: "ship", filename: "ship.png")],
+    ///     sounds: [(tag: "crash", filename: "crash.ogg")],
+    ///     fonts: [(tag: "hud", filename: "FiraMono-Medium.ttf")],
+    ///     sprite_sheets: [(
+    ///         tag: "flappy", filename: "flappy_sprite_sheet.png",
+    ///         sprite_width: 62.0, sprite_height: 65.0, sprites_x: 4, sprites_y: 1,
+    ///     )],
+    ///     sprite_atlases: [(tag: "hero", filename: "hero.png", frames: "hero.json")],
+    ///     packed_atlases: [(tag: "pickups", images: [(tag: "fuel", filename: "fuel.png")])],
+    ///     animations: [(tag: "hero_animations", filename: "hero.anim.ron")],
+    /// )
+    /// ```
+    #[cfg(feature = "serde")]
+    pub fn from_manifest<P: AsRef<std::path::Path>>(path: P) -> anyhow::Result<Self> {
+        super::asset_manifest::AssetManifest::load(path.as_ref())?.add_to(
+            Self::new(),
+            AssetManager::asset_exists,
+            AssetManager::read_asset,
+        )
+    }
+
+    pub(crate) fn push_asset(&mut self, tag: String, filename: String, asset_type: AssetType) {
+        match &self.current_group {
+            Some(group) => self
+                .groups
+                .entry(group.clone())
+                .or_default()
+                .push((tag, filename, asset_type)),
+            None => self.asset_list.push((tag, filename, asset_type)),
         }
     }
 
@@ -52,12 +118,17 @@ impl AssetManager {
         Ok(())
     }
 
+    #[cfg(feature = "serde")]
+    fn read_asset(filename: &str) -> anyhow::Result<String> {
+        let path = std::env::current_dir()?.join("assets").join(filename);
+        Ok(std::fs::read_to_string(path)?)
+    }
+
     pub fn add_image<S: ToString>(mut self, tag: S, filename: S) -> anyhow::Result<Self> {
         let filename = filename.to_string();
         AssetManager::asset_exists(&filename)?;
 
-        self.asset_list
-            .push((tag.to_string(), filename, AssetType::Image));
+        self.push_asset(tag.to_string(), filename, AssetType::Image);
         Ok(self)
     }
 
@@ -65,12 +136,45 @@ impl AssetManager {
         let filename = filename.to_string();
         AssetManager::asset_exists(&filename)?;
 
-        self.asset_list
-            .push((tag.to_string(), filename, AssetType::Sound));
+        self.push_asset(tag.to_string(), filename, AssetType::Sound);
+        Ok(self)
+    }
+
+    /// Adds a font (`.ttf` or `.otf`), which can be used for text with
+    /// `AssetStore::font`.
+    pub fn add_font<S: ToString>(mut self, tag: S, filename: S) -> anyhow::Result<Self> {
+        let filename = filename.to_string();
+        AssetManager::asset_exists(&filename)?;
+
+        self.push_asset(tag.to_string(), filename, AssetType::Font);
         Ok(self)
     }
 
     pub fn add_sprite_sheet<S: ToString>(
+        self,
+        tag: S,
+        filename: S,
+        sprite_width: f32,
+        sprite_height: f32,
+        sprites_x: usize,
+        sprites_y: usize,
+    ) -> anyhow::Result<Self> {
+        self.add_padded_sprite_sheet(
+            tag,
+            filename,
+            sprite_width,
+            sprite_height,
+            sprites_x,
+            sprites_y,
+            Vec2::ZERO,
+            Vec2::ZERO,
+        )
+    }
+
+    /// Adds a sprite sheet whose sprites are separated by `padding` pixels,
+    /// with the first sprite `offset` pixels from the top-left corner.
+    #[allow(clippy::too_many_arguments)]
+    pub fn add_padded_sprite_sheet<S: ToString>(
         mut self,
         tag: S,
         filename: S,
@@ -78,19 +182,84 @@ impl AssetManager {
         sprite_height: f32,
         sprites_x: usize,
         sprites_y: usize,
+        padding: Vec2,
+        offset: Vec2,
     ) -> anyhow::Result<Self> {
         let filename = filename.to_string();
         AssetManager::asset_exists(&filename)?;
 
-        self.asset_list.push((
+        self.push_asset(
             tag.to_string(),
             filename,
-            AssetType::SpriteSheet {
+            AssetType::SpriteSheet(SpriteSheetLayout::Grid {
                 title_size: Vec2::new(sprite_width, sprite_height),
                 sprites_x,
                 sprites_y,
-            },
-        ));
+                padding,
+                offset,
+            }),
+        );
+        Ok(self)
+    }
+
+    /// Packs several images into a single texture once they have loaded,
+    /// so that drawing them doesn't need a texture switch each time. The
+    /// images keep their own tags: `spawn_image!` and
+    /// `AssetStore::get_sprite` draw them from the packed texture.
+    ///
+    /// # Arguments
+    ///
+    /// * `tag` - the tag of the packed atlas.
+    /// * `images` - the `(tag, filename)` of each image to pack.
+    pub fn add_packed_atlas<S: ToString>(
+        mut self,
+        tag: S,
+        images: &[(S, S)],
+    ) -> anyhow::Result<Self> {
+        let mut tags = Vec::with_capacity(images.len());
+        for (image_tag, filename) in images {
+            let filename = filename.to_string();
+            AssetManager::asset_exists(&filename)?;
+            self.push_asset(image_tag.to_string(), filename, AssetType::Image);
+            tags.push(image_tag.to_string());
+        }
+        self.push_asset(tag.to_string(), String::new(), AssetType::PackedAtlas(tags));
+        Ok(self)
+    }
+
+    /// Adds a file of animations (see [`AnimationSet`](crate::AnimationSet)),
+    /// named `.anim.ron` or `.anim.json`. Its animations are added to the
+    /// [`Animations`](crate::Animations) resource once it has loaded, and
+    /// updated when it is hot reloaded. Requires the `serde` feature.
+    #[cfg(feature = "serde")]
+    pub fn add_animations<S: ToString>(mut self, tag: S, filename: S) -> anyhow::Result<Self> {
+        let filename = filename.to_string();
+        AssetManager::asset_exists(&filename)?;
+
+        self.push_asset(tag.to_string(), filename, AssetType::Animations);
+        Ok(self)
+    }
+
+    /// Adds a sprite sheet whose frames are listed in a JSON file, as
+    /// exported by TexturePacker or Aseprite (in either "hash" or "array"
+    /// format). Frames can be found by name with
+    /// `AssetStore::get_frame_index`. Requires the `serde` feature.
+    #[cfg(feature = "serde")]
+    pub fn add_sprite_atlas<S: ToString>(
+        mut self,
+        tag: S,
+        filename: S,
+        frames_filename: S,
+    ) -> anyhow::Result<Self> {
+        let filename = filename.to_string();
+        let frames_filename = frames_filename.to_string();
+        AssetManager::asset_exists(&filename)?;
+        AssetManager::asset_exists(&frames_filename)?;
+
+        let layout = AssetManager::read_asset(&frames_filename)
+            .and_then(|frames| super::sprite_atlas::parse_frames(&frames_filename, &frames))
+            .map_err(|e| anyhow::anyhow!("unable to parse {frames_filename}: {e}"))?;
+        self.push_asset(tag.to_string(), filename, AssetType::SpriteSheet(layout));
         Ok(self)
     }
 }
@@ -99,6 +268,40 @@ impl Plugin for AssetManager {
     fn build(&self, app: &mut App) {
         app.insert_resource(self.clone());
         //app.add_systems(Startup, setup);
+        app.add_event::<crate::AnimationEvent>();
+        app.add_event::<crate::AnimationFinished>();
+        #[cfg(feature = "serde")]
+        {
+            app.init_asset::<crate::AnimationSet>()
+                .register_asset_loader(super::animation_file::AnimationSetLoader)
+                .init_resource::<crate::Animations>()
+                .add_systems(Update, super::animation_file::sync_animations);
+        }
+        if self.hot_reload {
+            if !cfg!(feature = "hot_reload") {
+                warn!("Hot reloading needs my_library's `hot_reload` feature to watch for changes");
+            }
+            app.add_event::<AssetReloaded>();
+            app.add_systems(Update, super::hot_reload::reload_assets);
+            #[cfg(feature = "serde")]
+            app.init_asset::<super::sprite_atlas::SpriteAtlasFrames>()
+                .register_asset_loader(super::sprite_atlas::SpriteAtlasFramesLoader)
+                .add_systems(Update, super::sprite_atlas::reload_frames);
+        }
+    }
+
+    fn finish(&self, app: &mut App) {
+        // The AssetPlugin may be added after the AssetManager, so its asset
+        // folder is only known once every plugin has been built.
+        let file_path = app
+            .get_added_plugins::<AssetPlugin>()
+            .first()
+            .map(|asset_plugin| asset_plugin.file_path.clone());
+        if let Some(file_path) = file_path
+            && let Some(mut asset_manager) = app.world_mut().get_resource_mut::<AssetManager>()
+        {
+            asset_manager.file_path = file_path;
+        }
     }
 }
 
@@ -107,43 +310,17 @@ pub(crate) fn setup_asset_store(
     commands: &mut Commands,
     asset_server: &AssetServer,
 ) -> AssetStore {
-    let mut assets = AssetStore {
-        asset_index: bevy::platform::collections::HashMap::new(),
-        atlases_to_build: vec![],
-        atlases: bevy::platform::collections::HashMap::new(),
-    };
+    let mut assets = AssetStore::new(asset_server.clone());
+    assets.file_path = asset_resource.file_path.clone();
 
     asset_resource
         .asset_list
         .iter()
-        .for_each(|(tag, filename, asset_type)| match asset_type {
-            AssetType::SpriteSheet {
-                title_size,
-                sprites_x,
-                sprites_y,
-            } => {
-                let image_handle = asset_server.load_untyped(filename);
-                let base_tag = format!("{tag}_base");
-                assets.asset_index.insert(base_tag.clone(), image_handle);
-
-                assets.atlases_to_build.push(crate::FutureAtlas {
-                    tag: tag.clone(),
-                    texture_tag: base_tag,
-                    title_size: *title_size,
-                    sprites_x: *sprites_x,
-                    sprites_y: *sprites_y,
-                });
-            }
-            _ => {
-                // Most asset types don't reuire a separate loader
-                assets
-                    .asset_index
-                    .insert(tag.clone(), asset_server.load_untyped(filename));
-            }
-        });
+        .for_each(|(tag, filename, asset_type)| assets.load(tag, filename, asset_type));
 
     commands.remove_resource::<AssetManager>();
     commands.insert_resource(assets.clone());
+    commands.insert_resource(AssetGroups::new(asset_resource.groups.clone()));
 
     assets
 }
@@ -0,0 +1,298 @@
+use std::path::Path;
+
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use super::asset_manager::{AssetManager, AssetType};
+use crate::SpriteSheetLayout;
+
+/// The contents of an asset manifest file. Every list is optional.
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct AssetManifest {
+    #[serde(default)]
+    images: Vec<ManifestAsset>,
+    #[serde(default)]
+    sounds: Vec<ManifestAsset>,
+    #[serde(default)]
+    fonts: Vec<ManifestAsset>,
+    #[serde(default)]
+    sprite_sheets: Vec<ManifestSpriteSheet>,
+    #[serde(default)]
+    sprite_atlases: Vec<ManifestSpriteAtlas>,
+    #[serde(default)]
+    packed_atlases: Vec<ManifestPackedAtlas>,
+    #[serde(default)]
+    animations: Vec<ManifestAsset>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct ManifestAsset {
+    tag: String,
+    filename: String,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct ManifestSpriteSheet {
+    tag: String,
+    filename: String,
+    sprite_width: f32,
+    sprite_height: f32,
+    sprites_x: usize,
+    sprites_y: usize,
+    #[serde(default)]
+    padding: (f32, f32),
+    #[serde(default)]
+    offset: (f32, f32),
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct ManifestPackedAtlas {
+    tag: String,
+    images: Vec<ManifestAsset>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct ManifestSpriteAtlas {
+    tag: String,
+    filename: String,
+    frames: String,
+}
+
+impl AssetManifest {
+    pub(crate) fn load(path: &Path) -> anyhow::Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| anyhow::anyhow!("unable to read {}: {e}", path.display()))?;
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("ron") => ron::from_str(&contents)
+                .map_err(|e| anyhow::anyhow!("unable to parse {}: {e}", path.display())),
+            Some("json") => serde_json::from_str(&contents)
+                .map_err(|e| anyhow::anyhow!("unable to parse {}: {e}", path.display())),
+            _ => Err(anyhow::anyhow!(
+                "{} is not a .ron or .json manifest",
+                path.display()
+            )),
+        }
+    }
+
+    /// Adds every asset in the manifest to `manager`. Every asset is
+    /// checked, and all of the missing files (and sprite atlas frame files
+    /// that can't be parsed) are reported together.
+    pub(crate) fn add_to(
+        self,
+        mut manager: AssetManager,
+        asset_exists: impl Fn(&str) -> anyhow::Result<()>,
+        read_asset: impl Fn(&str) -> anyhow::Result<String>,
+    ) -> anyhow::Result<AssetManager> {
+        let images = self
+            .images
+            .into_iter()
+            .map(|asset| (asset.tag, asset.filename, AssetType::Image));
+        let sounds = self
+            .sounds
+            .into_iter()
+            .map(|asset| (asset.tag, asset.filename, AssetType::Sound));
+        let fonts = self
+            .fonts
+            .into_iter()
+            .map(|asset| (asset.tag, asset.filename, AssetType::Font));
+        let animations = self
+            .animations
+            .into_iter()
+            .map(|asset| (asset.tag, asset.filename, AssetType::Animations));
+        let sprite_sheets = self.sprite_sheets.into_iter().map(|sheet| {
+            (
+                sheet.tag,
+                sheet.filename,
+                AssetType::SpriteSheet(SpriteSheetLayout::Grid {
+                    title_size: Vec2::new(sheet.sprite_width, sheet.sprite_height),
+                    sprites_x: sheet.sprites_x,
+                    sprites_y: sheet.sprites_y,
+                    padding: sheet.padding.into(),
+                    offset: sheet.offset.into(),
+                }),
+            )
+        });
+
+        let mut errors = vec![];
+        for (tag, filename, asset_type) in images
+            .chain(sounds)
+            .chain(fonts)
+            .chain(animations)
+            .chain(sprite_sheets)
+        {
+            match asset_exists(&filename) {
+                Ok(()) => manager.push_asset(tag, filename, asset_type),
+                Err(e) => errors.push(e.to_string()),
+            }
+        }
+        for atlas in self.sprite_atlases {
+            let found = [&atlas.filename, &atlas.frames]
+                .map(|filename| asset_exists(filename).map_err(|e| errors.push(e.to_string())));
+            if found.iter().any(Result::is_err) {
+                continue;
+            }
+            match read_asset(&atlas.frames)
+                .and_then(|json| super::sprite_atlas::parse_frames(&atlas.frames, &json))
+            {
+                Ok(layout) => {
+                    manager.push_asset(atlas.tag, atlas.filename, AssetType::SpriteSheet(layout))
+                }
+                Err(e) => errors.push(format!("unable to parse {}: {e}", atlas.frames)),
+            }
+        }
+        for atlas in self.packed_atlases {
+            let mut tags = Vec::with_capacity(atlas.images.len());
+            for image in atlas.images {
+                match asset_exists(&image.filename) {
+                    Ok(()) => {
+                        manager.push_asset(image.tag.clone(), image.filename, AssetType::Image)
+                    }
+                    Err(e) => errors.push(e.to_string()),
+                }
+                tags.push(image.tag);
+            }
+            manager.push_asset(atlas.tag, String::new(), AssetType::PackedAtlas(tags));
+        }
+
+        if errors.is_empty() {
+            Ok(manager)
+        } else {
+            Err(anyhow::anyhow!(
+                "{} asset(s) missing or invalid:\n{}",
+                errors.len(),
+                errors.join("\n")
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MANIFEST: &str = r#"(
+        images: [(tag: "ship", filename: "ship.png"), (tag: "ground", filename: "ground.png")],
+        sounds: [(tag: "crash", filename: "crash.ogg")],
+        fonts: [(tag: "hud", filename: "hud.ttf")],
+        sprite_sheets: [(
+            tag: "flappy", filename: "flappy.png",
+            sprite_width: 62.0, sprite_height: 65.0, sprites_x: 4, sprites_y: 1,
+            padding: (2.0, 2.0),
+        )],
+        sprite_atlases: [(tag: "hero", filename: "hero.png", frames: "hero.json")],
+        packed_atlases: [(tag: "pickups", images: [(tag: "fuel", filename: "fuel.png")])],
+        animations: [(tag: "hero_animations", filename: "hero.anim.ron")],
+    )"#;
+
+    const FRAMES: &str = r#"{ "frames": [
+        { "filename": "idle", "frame": { "x": 0, "y": 0, "w": 8, "h": 8 } }
+    ] }"#;
+
+    #[test]
+    fn test_manifest_adds_assets() {
+        let manifest: AssetManifest = ron::from_str(MANIFEST).unwrap();
+        let manager = manifest
+            .add_to(AssetManager::new(), |_| Ok(()), |_| Ok(FRAMES.to_string()))
+            .unwrap();
+        let asset_type = |tag: &str| {
+            manager
+                .asset_list
+                .iter()
+                .find(|(asset_tag, ..)| asset_tag == tag)
+                .map(|(_, _, asset_type)| asset_type)
+        };
+        for tag in ["ship", "ground", "fuel"] {
+            assert!(matches!(asset_type(tag), Some(AssetType::Image)));
+        }
+        assert!(matches!(asset_type("crash"), Some(AssetType::Sound)));
+        assert!(matches!(asset_type("hud"), Some(AssetType::Font)));
+        assert!(matches!(
+            asset_type("hero_animations"),
+            Some(AssetType::Animations)
+        ));
+        assert!(matches!(
+            asset_type("flappy"),
+            Some(AssetType::SpriteSheet(SpriteSheetLayout::Grid {
+                sprites_x: 4,
+                ..
+            }))
+        ));
+        assert!(matches!(
+            asset_type("hero"),
+            Some(AssetType::SpriteSheet(SpriteSheetLayout::Frames { .. }))
+        ));
+        assert!(matches!(
+            asset_type("pickups"),
+            Some(AssetType::PackedAtlas(tags)) if tags == &["fuel"]
+        ));
+    }
+
+    #[test]
+    fn test_manifest_reports_unparseable_frames_with_missing_files() {
+        let manifest: AssetManifest = ron::from_str(MANIFEST).unwrap();
+        let error = manifest
+            .add_to(
+                AssetManager::new(),
+                |filename| {
+                    if filename == "crash.ogg" {
+                        Err(anyhow::anyhow!("{filename} not found"))
+                    } else {
+                        Ok(())
+                    }
+                },
+                |_| Ok("not json".to_string()),
+            )
+            .err()
+            .unwrap()
+            .to_string();
+        assert!(error.starts_with("2 asset(s) missing"));
+        assert!(error.contains("crash.ogg not found"));
+        assert!(error.contains("unable to parse hero.json"));
+    }
+
+    #[test]
+    fn test_manifest_reports_every_missing_file() {
+        let manifest: AssetManifest = ron::from_str(MANIFEST).unwrap();
+        let error = manifest
+            .add_to(
+                AssetManager::new(),
+                |filename| {
+                    if filename == "ship.png" {
+                        Ok(())
+                    } else {
+                        Err(anyhow::anyhow!("{filename} not found"))
+                    }
+                },
+                |_| Ok(FRAMES.to_string()),
+            )
+            .err()
+            .unwrap()
+            .to_string();
+        assert!(error.starts_with("8 asset(s) missing"));
+        for filename in [
+            "ground.png",
+            "crash.ogg",
+            "hud.ttf",
+            "flappy.png",
+            "hero.png",
+            "hero.json",
+            "fuel.png",
+            "hero.anim.ron",
+        ] {
+            assert!(error.contains(filename));
+        }
+    }
+
+    #[test]
+    fn test_json_manifest() {
+        let json = r#"{ "sounds": [{ "tag": "flap", "filename": "flap.ogg" }] }"#;
+        let manifest: AssetManifest = serde_json::from_str(json).unwrap();
+        assert_eq!(manifest.sounds.len(), 1);
+        assert!(manifest.images.is_empty());
+    }
+}
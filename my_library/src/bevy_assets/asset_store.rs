@@ -1,5 +1,9 @@
+use std::fmt;
+
+use super::asset_manager::AssetType;
+use crate::{AudioChannel, SoundEmitter, SoundTag};
 use bevy::{
-    asset::{Asset, LoadedUntypedAsset},
+    asset::{Asset, LoadState, LoadedUntypedAsset},
     platform::collections::HashMap,
     prelude::*,
 };
@@ -10,47 +14,517 @@ pub type AssetResource<'w> = Res<'w, LoadedAssets>;
 #[derive(Resource, Clone)]
 pub struct AssetStore {
     pub(crate) asset_index: HashMap<String, Handle<LoadedUntypedAsset>>,
+    pub(crate) filenames: HashMap<String, String>,
     pub(crate) atlases_to_build: Vec<FutureAtlas>,
     pub(crate) atlases: HashMap<String, (Handle<Image>, Handle<TextureAtlasLayout>)>,
+    pub(crate) packed_to_build: Vec<FuturePackedAtlas>,
+    pub(crate) packed_images: HashMap<String, (String, usize)>,
+    pub(crate) asset_server: AssetServer,
+    /// The folder assets are loaded from, as set by `AssetPlugin::file_path`.
+    pub(crate) file_path: String,
 }
 
 impl AssetStore {
+    pub(crate) fn new(asset_server: AssetServer) -> Self {
+        Self {
+            asset_index: HashMap::new(),
+            filenames: HashMap::new(),
+            atlases_to_build: vec![],
+            atlases: HashMap::new(),
+            packed_to_build: vec![],
+            packed_images: HashMap::new(),
+            asset_server,
+            file_path: AssetPlugin::default().file_path,
+        }
+    }
+
     pub fn get_handle<T>(&self, index: &str, assets: &LoadedAssets) -> Option<Handle<T>>
     where
         T: Asset,
     {
-        if let Some(handle_untyped) = self.asset_index.get(index) {
-            if let Some(handle) = assets.get(handle_untyped) {
-                return Some(handle.handle.clone().typed::<T>());
+        self.try_get_handle(index, assets).ok()
+    }
+
+    /// Like `get_handle`, but reports whether the tag is unknown, still
+    /// loading, failed to load or holds a different type of asset.
+    pub fn try_get_handle<T>(
+        &self,
+        index: &str,
+        assets: &LoadedAssets,
+    ) -> Result<Handle<T>, AssetError>
+    where
+        T: Asset,
+    {
+        let Some(handle_untyped) = self.asset_index.get(index) else {
+            return Err(AssetError::Unknown {
+                tag: index.to_string(),
+            });
+        };
+        match assets.get(handle_untyped) {
+            Some(handle) => {
+                handle
+                    .handle
+                    .clone()
+                    .try_typed::<T>()
+                    .map_err(|_| AssetError::WrongType {
+                        tag: index.to_string(),
+                        expected: std::any::type_name::<T>(),
+                    })
             }
-            None
-        } else {
-            None
+            None => Err(self.not_loaded(index, handle_untyped)),
+        }
+    }
+
+    /// Returns a `Sprite` showing the image registered under `index`. Images
+    /// added with `AssetManager::add_packed_atlas` are drawn from their
+    /// packed atlas.
+    pub fn get_sprite(&self, index: &str, assets: &LoadedAssets) -> Option<Sprite> {
+        self.try_get_sprite(index, assets).ok()
+    }
+
+    /// Like `get_sprite`, but reports why the image isn't available.
+    pub fn try_get_sprite(&self, index: &str, assets: &LoadedAssets) -> Result<Sprite, AssetError> {
+        if let Some((atlas, atlas_index)) = self.packed_images.get(index)
+            && let Some((image, layout)) = self.atlases.get(atlas)
+        {
+            return Ok(Sprite::from_atlas_image(
+                image.clone(),
+                TextureAtlas {
+                    layout: layout.clone(),
+                    index: *atlas_index,
+                },
+            ));
         }
+        Ok(Sprite::from_image(self.try_get_handle(index, assets)?))
+    }
+
+    /// Returns the font registered with `AssetManager::add_font`, or `None`
+    /// if the tag is unknown or the font hasn't loaded yet.
+    pub fn font(&self, index: &str, assets: &LoadedAssets) -> Option<Handle<Font>> {
+        self.get_handle(index, assets)
     }
 
     pub fn play(&self, sound_name: &str, commands: &mut Commands, assets: &LoadedAssets) {
-        let sound_handle: Handle<AudioSource> = self.get_handle(sound_name, assets).unwrap();
-        commands.spawn(AudioPlayer::new(sound_handle.clone()));
+        self.play_on(sound_name, AudioChannel::Sfx, commands, assets);
+    }
+
+    /// Like `play`, but returns an error instead of panicking.
+    pub fn try_play(
+        &self,
+        sound_name: &str,
+        commands: &mut Commands,
+        assets: &LoadedAssets,
+    ) -> Result<(), AssetError> {
+        self.try_play_on(sound_name, AudioChannel::Sfx, commands, assets)
+    }
+
+    /// Plays a sound on an [`AudioChannel`], mixed by the [`GameAudio`](crate::GameAudio)
+    /// settings. The player is despawned once the sound finishes.
+    pub fn play_on(
+        &self,
+        sound_name: &str,
+        channel: AudioChannel,
+        commands: &mut Commands,
+        assets: &LoadedAssets,
+    ) {
+        if let Err(error) = self.try_play_on(sound_name, channel, commands, assets) {
+            panic!("{error}");
+        }
+    }
+
+    /// Like `play_on`, but returns an error instead of panicking.
+    pub fn try_play_on(
+        &self,
+        sound_name: &str,
+        channel: AudioChannel,
+        commands: &mut Commands,
+        assets: &LoadedAssets,
+    ) -> Result<(), AssetError> {
+        let sound_handle: Handle<AudioSource> = self.try_get_handle(sound_name, assets)?;
+        commands.spawn((
+            AudioPlayer::new(sound_handle),
+            PlaybackSettings::DESPAWN,
+            channel,
+            SoundTag(sound_name.to_string()),
+        ));
+        Ok(())
+    }
+
+    /// Plays a sound effect from `position`, attenuated and panned relative
+    /// to the [`SoundListener`](crate::SoundListener). Returns the sound's
+    /// entity, so that a different [`SoundEmitter`] can be inserted.
+    pub fn play_at(
+        &self,
+        sound_name: &str,
+        position: Vec2,
+        commands: &mut Commands,
+        assets: &LoadedAssets,
+    ) -> Entity {
+        self.try_play_at(sound_name, position, commands, assets)
+            .unwrap_or_else(|error| panic!("{error}"))
+    }
+
+    /// Like `play_at`, but returns an error instead of panicking.
+    pub fn try_play_at(
+        &self,
+        sound_name: &str,
+        position: Vec2,
+        commands: &mut Commands,
+        assets: &LoadedAssets,
+    ) -> Result<Entity, AssetError> {
+        let sound_handle: Handle<AudioSource> = self.try_get_handle(sound_name, assets)?;
+        Ok(commands
+            .spawn((
+                AudioPlayer::new(sound_handle),
+                PlaybackSettings::DESPAWN,
+                AudioChannel::Sfx,
+                SoundTag(sound_name.to_string()),
+                SoundEmitter::default(),
+                Transform::from_translation(position.extend(0.0)),
+            ))
+            .id())
+    }
+
+    /// Like `play_at`, but the sound follows `entity` as it moves. The
+    /// sound stops if the entity is despawned.
+    pub fn play_from(
+        &self,
+        sound_name: &str,
+        entity: Entity,
+        commands: &mut Commands,
+        assets: &LoadedAssets,
+    ) -> Entity {
+        self.try_play_from(sound_name, entity, commands, assets)
+            .unwrap_or_else(|error| panic!("{error}"))
+    }
+
+    /// Like `play_from`, but returns an error instead of panicking.
+    pub fn try_play_from(
+        &self,
+        sound_name: &str,
+        entity: Entity,
+        commands: &mut Commands,
+        assets: &LoadedAssets,
+    ) -> Result<Entity, AssetError> {
+        let sound = self.try_play_at(sound_name, Vec2::ZERO, commands, assets)?;
+        commands.entity(sound).insert(ChildOf(entity));
+        Ok(sound)
     }
 
     pub fn get_atlas_handle(
         &self,
         index: &str,
     ) -> Option<(Handle<Image>, Handle<TextureAtlasLayout>)> {
+        self.try_get_atlas_handle(index).ok()
+    }
+
+    /// Like `get_atlas_handle`, but reports why the atlas isn't available.
+    pub fn try_get_atlas_handle(
+        &self,
+        index: &str,
+    ) -> Result<(Handle<Image>, Handle<TextureAtlasLayout>), AssetError> {
         if let Some(handle) = self.atlases.get(index) {
-            Some(handle.clone())
-        } else {
-            None
+            return Ok(handle.clone());
+        }
+        // Atlases are only built once every asset has loaded, so report on
+        // the image behind it.
+        self.atlases_to_build
+            .iter()
+            .find(|atlas| atlas.tag == index)
+            .and_then(|atlas| self.asset_index.get(&atlas.texture_tag))
+            .map(|handle| Err(self.not_loaded(index, handle)))
+            .unwrap_or_else(|| {
+                Err(AssetError::Unknown {
+                    tag: index.to_string(),
+                })
+            })
+    }
+
+    /// Finds a named frame in a sprite sheet loaded with
+    /// `AssetManager::add_sprite_atlas`, returning its index for use in a
+    /// `TextureAtlas`.
+    pub fn get_frame_index(&self, index: &str, frame: &str) -> Option<usize> {
+        self.atlases_to_build
+            .iter()
+            .find(|atlas| atlas.tag == index)
+            .and_then(|atlas| atlas.layout.frame_index(frame))
+    }
+
+    pub(crate) fn load(&mut self, tag: &str, filename: &str, asset_type: &AssetType) {
+        match asset_type {
+            AssetType::PackedAtlas(images) => {
+                // The images are loaded separately, and packed once they
+                // have all arrived.
+                self.packed_to_build.push(FuturePackedAtlas {
+                    tag: tag.to_string(),
+                    images: images.clone(),
+                });
+            }
+            AssetType::SpriteSheet(layout) => {
+                let base_tag = format!("{tag}_base");
+                self.load_file(&base_tag, filename);
+
+                self.atlases_to_build.push(FutureAtlas {
+                    tag: tag.to_string(),
+                    texture_tag: base_tag,
+                    layout: layout.clone(),
+                });
+            }
+            _ => {
+                // Most asset types don't reuire a separate loader
+                self.load_file(tag, filename);
+            }
+        }
+    }
+
+    fn load_file(&mut self, tag: &str, filename: &str) {
+        let handle = self.asset_server.load_untyped(filename);
+        self.asset_index.insert(tag.to_string(), handle);
+        self.filenames.insert(tag.to_string(), filename.to_string());
+    }
+
+    /// Starts loading an index tag's file again, for example after it
+    /// failed to load.
+    pub(crate) fn reload(&mut self, tag: &str) {
+        if let Some(filename) = self.filenames.get(tag).cloned() {
+            self.load_file(tag, &filename);
+        }
+    }
+
+    /// Forgets an asset. Bevy frees it once nothing else holds a handle.
+    pub(crate) fn unload(&mut self, tag: &str, asset_type: &AssetType) {
+        match asset_type {
+            AssetType::PackedAtlas(_) => {
+                self.packed_to_build.retain(|atlas| atlas.tag != tag);
+                self.packed_images.retain(|_, (atlas, _)| atlas != tag);
+                self.atlases.remove(tag);
+            }
+            AssetType::SpriteSheet(_) => {
+                let base_tag = format!("{tag}_base");
+                self.asset_index.remove(&base_tag);
+                self.filenames.remove(&base_tag);
+                self.atlases_to_build.retain(|atlas| atlas.tag != tag);
+                self.atlases.remove(tag);
+            }
+            _ => {
+                self.asset_index.remove(tag);
+                self.filenames.remove(tag);
+            }
+        }
+    }
+
+    fn not_loaded(&self, index: &str, handle: &Handle<LoadedUntypedAsset>) -> AssetError {
+        match self.asset_server.get_load_state(handle.id()) {
+            Some(LoadState::Failed(error)) => AssetError::Failed {
+                tag: index.to_string(),
+                reason: error.to_string(),
+            },
+            _ => AssetError::Loading {
+                tag: index.to_string(),
+            },
         }
     }
 }
 
+/// Describes why an asset could not be retrieved from the [`AssetStore`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AssetError {
+    /// No asset was added to the `AssetManager` with this tag.
+    Unknown {
+        /// The requested tag.
+        tag: String,
+    },
+    /// The asset exists, but hasn't finished loading yet.
+    Loading {
+        /// The requested tag.
+        tag: String,
+    },
+    /// The asset could not be loaded.
+    Failed {
+        /// The requested tag.
+        tag: String,
+        /// Why loading failed, as reported by Bevy.
+        reason: String,
+    },
+    /// The asset was loaded, but isn't of the requested type (for example,
+    /// an image requested as a sound).
+    WrongType {
+        /// The requested tag.
+        tag: String,
+        /// The name of the requested asset type.
+        expected: &'static str,
+    },
+}
+
+impl fmt::Display for AssetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unknown { tag } => write!(f, "no asset was registered with the tag '{tag}'"),
+            Self::Loading { tag } => write!(f, "asset '{tag}' has not finished loading"),
+            Self::Failed { tag, reason } => write!(f, "asset '{tag}' failed to load: {reason}"),
+            Self::WrongType { tag, expected } => write!(f, "asset '{tag}' is not a {expected}"),
+        }
+    }
+}
+
+impl std::error::Error for AssetError {}
+
+#[derive(Clone)]
+pub(crate) struct FuturePackedAtlas {
+    pub(crate) tag: String,
+    pub(crate) images: Vec<String>,
+}
+
 #[derive(Clone)]
 pub(crate) struct FutureAtlas {
     pub(crate) tag: String,
     pub(crate) texture_tag: String,
-    pub(crate) title_size: Vec2,
-    pub(crate) sprites_x: usize,
-    pub(crate) sprites_y: usize,
+    pub(crate) layout: SpriteSheetLayout,
+}
+
+/// Where the sprites are in a sprite sheet's image.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum SpriteSheetLayout {
+    /// Equally sized sprites in a grid, read left to right and top to bottom.
+    Grid {
+        title_size: Vec2,
+        sprites_x: usize,
+        sprites_y: usize,
+        padding: Vec2,
+        offset: Vec2,
+    },
+    /// Named frames of any size, for example from a TexturePacker or
+    /// Aseprite export. Frames are indexed in the order they are listed.
+    /// Only sprite atlases, which need the `serde` feature, use them.
+    #[cfg_attr(not(feature = "serde"), allow(dead_code))]
+    Frames {
+        size: UVec2,
+        frames: Vec<(String, URect)>,
+        /// The JSON file the frames were read from.
+        file: String,
+    },
+}
+
+impl SpriteSheetLayout {
+    pub(crate) fn build(&self) -> TextureAtlasLayout {
+        match self {
+            Self::Grid {
+                title_size,
+                sprites_x,
+                sprites_y,
+                padding,
+                offset,
+            } => TextureAtlasLayout::from_grid(
+                title_size.as_uvec2(),
+                *sprites_x as u32,
+                *sprites_y as u32,
+                Some(padding.as_uvec2()),
+                Some(offset.as_uvec2()),
+            ),
+            Self::Frames { size, frames, .. } => {
+                let mut layout = TextureAtlasLayout::new_empty(*size);
+                for (_, frame) in frames {
+                    layout.add_texture(*frame);
+                }
+                layout
+            }
+        }
+    }
+
+    pub(crate) fn frame_index(&self, name: &str) -> Option<usize> {
+        match self {
+            Self::Grid { .. } => None,
+            Self::Frames { frames, .. } => frames.iter().position(|(frame, _)| frame == name),
+        }
+    }
+
+    /// Fits the layout to an image that is now `image_size`, usually
+    /// because sprites were added to it. Grids keep their sprite size,
+    /// padding and offset, and gain or lose columns and rows. Frame lists
+    /// are only changed by editing their JSON file.
+    pub(crate) fn resize(&mut self, image_size: UVec2) {
+        if let Self::Grid {
+            title_size,
+            sprites_x,
+            sprites_y,
+            padding,
+            offset,
+        } = self
+        {
+            let padding = padding.as_uvec2();
+            let step = (title_size.as_uvec2() + padding).max(UVec2::ONE);
+            let sprites = (image_size + padding).saturating_sub(offset.as_uvec2()) / step;
+            *sprites_x = sprites.x as usize;
+            *sprites_y = sprites.y as usize;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_errors_name_tag_and_state() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()));
+        let asset_server = app.world().resource::<AssetServer>().clone();
+        let mut store = AssetStore::new(asset_server.clone());
+        store.asset_index.insert(
+            "missing".to_string(),
+            asset_server.load_untyped("does_not_exist.png"),
+        );
+
+        let loaded = app.world().resource::<LoadedAssets>();
+        assert_eq!(
+            store.try_get_handle::<Image>("ghost", loaded),
+            Err(AssetError::Unknown {
+                tag: "ghost".to_string()
+            })
+        );
+
+        let mut result = store.try_get_handle::<Image>("missing", loaded);
+        for _ in 0..500 {
+            if !matches!(result, Err(AssetError::Loading { .. })) {
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(5));
+            app.update();
+            result =
+                store.try_get_handle::<Image>("missing", app.world().resource::<LoadedAssets>());
+        }
+        let error = result.unwrap_err();
+        assert!(matches!(&error, AssetError::Failed { tag, .. } if tag == "missing"));
+        assert!(
+            error
+                .to_string()
+                .starts_with("asset 'missing' failed to load")
+        );
+    }
+
+    #[test]
+    fn test_wrong_type_is_an_error() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+            .init_asset::<Image>()
+            .init_asset::<AudioSource>();
+        let world = app.world_mut();
+        let mut store = AssetStore::new(world.resource::<AssetServer>().clone());
+        let image = world.resource_mut::<Assets<Image>>().add(Image::default());
+        let loaded = world
+            .resource_mut::<LoadedAssets>()
+            .add(LoadedUntypedAsset {
+                handle: image.clone().untyped(),
+            });
+        store.asset_index.insert("ship".to_string(), loaded);
+
+        let loaded = app.world().resource::<LoadedAssets>();
+        assert_eq!(store.try_get_handle::<Image>("ship", loaded), Ok(image));
+        let error = store
+            .try_get_handle::<AudioSource>("ship", loaded)
+            .unwrap_err();
+        assert!(matches!(&error, AssetError::WrongType { tag, .. } if tag == "ship"));
+        assert!(error.to_string().contains("AudioSource"));
+        assert!(store.get_handle::<AudioSource>("ship", loaded).is_none());
+    }
 }
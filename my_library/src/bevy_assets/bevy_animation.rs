@@ -1,40 +1,117 @@
+use std::time::Duration;
+
 use bevy::{app::Animation, log, platform::collections::HashMap, prelude::*};
 
+/// The most frames an animation can move through in one update.
+const MAX_FRAMES_PER_UPDATE: usize = 1000;
+
+/// The fastest an animation can play, relative to normal speed.
+const MAX_SPEED: f32 = 1000.0;
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize))]
 pub enum AnimationOption {
     None,
     NextFrame,
     GoToFrame(usize),
     SwitchToAnimation(String),
     PlaySound(String),
+    /// Sends an [`AnimationEvent`] with this name, e.g. to spawn a
+    /// projectile on the frame a character throws it.
+    Event(String),
+}
+
+/// What an animation does once a frame's delay is over, if none of the
+/// frame's actions moved it to another frame.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize))]
+pub enum AnimationMode {
+    /// Frames only change through their actions (`NextFrame`, `GoToFrame`
+    /// and `SwitchToAnimation`).
+    #[default]
+    Manual,
+    /// Plays the frames in order, starting again after the last one.
+    Loop,
+    /// Plays the frames in order and stops on the last one, sending an
+    /// [`AnimationFinished`] event.
+    Once,
+    /// Plays the frames forwards, then backwards, and so on.
+    PingPong,
+}
+
+/// Sent when an [`AnimationOption::Event`] frame action runs.
+#[derive(Event, Clone, Debug, PartialEq, Eq)]
+pub struct AnimationEvent {
+    /// The animated entity.
+    pub entity: Entity,
+    /// The event's name.
+    pub name: String,
+}
+
+/// Sent when an [`AnimationMode::Once`] animation reaches its end.
+#[derive(Event, Clone, Debug, PartialEq, Eq)]
+pub struct AnimationFinished {
+    /// The animated entity.
+    pub entity: Entity,
+    /// The animation that finished.
+    pub animation: String,
 }
 
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize))]
 pub struct AnimationFrame {
-    sprite_index: usize,
-    delay_ms: u128,
-    action: Vec<AnimationOption>,
+    pub(crate) sprite_index: usize,
+    #[cfg_attr(
+        feature = "serde",
+        serde(rename = "delay_ms", deserialize_with = "deserialize_millis")
+    )]
+    pub(crate) delay: Duration,
+    #[cfg_attr(feature = "serde", serde(rename = "actions", default))]
+    pub(crate) action: Vec<AnimationOption>,
+}
+
+#[cfg(feature = "serde")]
+fn deserialize_millis<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Duration, D::Error> {
+    <u64 as serde::Deserialize>::deserialize(deserializer).map(Duration::from_millis)
 }
 
 impl AnimationFrame {
     pub fn new(sprite_index: usize, delay_ms: u128, action: Vec<AnimationOption>) -> Self {
         Self {
             sprite_index,
-            delay_ms,
+            delay: Duration::from_millis(delay_ms.try_into().unwrap_or(u64::MAX)),
             action,
         }
     }
 }
 
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize))]
 pub struct PerFrameAnimation {
     pub frames: Vec<AnimationFrame>,
+    /// How the animation moves between frames.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub mode: AnimationMode,
 }
 
 impl PerFrameAnimation {
     pub fn new(frames: Vec<AnimationFrame>) -> Self {
-        Self { frames }
+        Self {
+            frames,
+            mode: AnimationMode::default(),
+        }
+    }
+
+    /// Sets how the animation moves between frames.
+    pub fn with_mode(mut self, mode: AnimationMode) -> Self {
+        self.mode = mode;
+        self
     }
 }
 
-#[derive(Resource)]
+#[derive(Resource, Default)]
 pub struct Animations(HashMap<String, PerFrameAnimation>);
 
 impl Animations {
@@ -46,13 +123,32 @@ impl Animations {
         self.0.insert(tag.to_string(), animation);
         self
     }
+
+    /// Returns the animation named `tag`, if there is one.
+    pub fn get(&self, tag: &str) -> Option<&PerFrameAnimation> {
+        self.0.get(tag)
+    }
+
+    /// Adds an animation, replacing any with the same name.
+    pub fn insert<S: ToString>(&mut self, tag: S, animation: PerFrameAnimation) {
+        self.0.insert(tag.to_string(), animation);
+    }
+
+    /// Removes the animation named `tag`.
+    pub fn remove(&mut self, tag: &str) -> Option<PerFrameAnimation> {
+        self.0.remove(tag)
+    }
 }
 
 #[derive(Component)]
 pub struct AnimationCycle {
     animation_tag: String,
     current_frame: usize,
-    timer: u128,
+    timer: Duration,
+    speed: f32,
+    paused: bool,
+    reversed: bool,
+    finished: bool,
 }
 
 impl AnimationCycle {
@@ -60,68 +156,243 @@ impl AnimationCycle {
         Self {
             animation_tag: animation_tag.to_string(),
             current_frame: 0,
-            timer: 0,
+            timer: Duration::ZERO,
+            speed: 1.0,
+            paused: false,
+            reversed: false,
+            finished: false,
         }
     }
 
+    /// Plays the animation `speed` times faster (or slower, below `1.0`).
+    pub fn with_speed(mut self, speed: f32) -> Self {
+        self.set_speed(speed);
+        self
+    }
+
+    /// Starts playing a different animation. Switching to the animation
+    /// that is already playing does nothing, unless it has finished, in
+    /// which case it plays again.
     pub fn switch<S: ToString>(&mut self, new: S) {
         let new = new.to_string();
-        if new != self.animation_tag {
-            self.animation_tag = new;
+        if new != self.animation_tag || self.finished {
+            self.restart(new);
+        }
+    }
+
+    /// Plays the current animation again from its first frame, for example
+    /// to repeat a finished [`AnimationMode::Once`] animation.
+    pub fn replay(&mut self) {
+        self.restart(self.animation_tag.clone());
+    }
+
+    fn restart(&mut self, animation_tag: String) {
+        self.animation_tag = animation_tag;
+        self.current_frame = 0;
+        self.timer = Duration::ZERO;
+        self.reversed = false;
+        self.finished = false;
+    }
+
+    /// The name of the animation that is playing.
+    pub fn animation(&self) -> &str {
+        &self.animation_tag
+    }
+
+    /// The playback speed, where `1.0` is normal speed.
+    pub fn speed(&self) -> f32 {
+        self.speed
+    }
+
+    /// Changes the playback speed, where `1.0` is normal speed. Speeds are
+    /// limited to between `0.0` and `1000.0`.
+    pub fn set_speed(&mut self, speed: f32) {
+        self.speed = if speed.is_nan() {
+            0.0
+        } else {
+            speed.clamp(0.0, MAX_SPEED)
+        };
+    }
+
+    /// Freezes the animation on its current frame.
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    /// Continues a paused animation.
+    pub fn resume(&mut self) {
+        self.paused = false;
+    }
+
+    /// Returns `true` if the animation is paused.
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Returns `true` once an [`AnimationMode::Once`] animation has reached
+    /// its end.
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// Warns and restarts from the first frame if `GoToFrame` or a reloaded,
+    /// shorter animation left `current_frame` out of range.
+    fn restart_if_out_of_range(&mut self, frame_count: usize) {
+        if frame_count > 0 && self.current_frame >= frame_count {
+            log::warn!(
+                "Animation Cycle [{}] has no frame {}, restarting from frame 0",
+                self.animation_tag,
+                self.current_frame
+            );
             self.current_frame = 0;
-            self.timer = 0;
         }
     }
+
+    /// Moves to the next frame according to `mode`, returning `true` if the
+    /// animation has just finished.
+    fn advance(&mut self, mode: AnimationMode, frame_count: usize) -> bool {
+        let last = frame_count.saturating_sub(1);
+        match mode {
+            AnimationMode::Manual => {}
+            AnimationMode::Loop => {
+                self.current_frame = if self.current_frame >= last {
+                    0
+                } else {
+                    self.current_frame + 1
+                };
+            }
+            AnimationMode::Once => {
+                if self.current_frame >= last {
+                    self.finished = true;
+                    return true;
+                }
+                self.current_frame += 1;
+            }
+            AnimationMode::PingPong => {
+                if self.current_frame >= last {
+                    self.reversed = true;
+                } else if self.current_frame == 0 {
+                    self.reversed = false;
+                }
+                self.current_frame = if self.reversed {
+                    self.current_frame.saturating_sub(1)
+                } else {
+                    self.current_frame + 1
+                };
+            }
+        }
+        false
+    }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn cycle_animations(
     animations: Res<Animations>,
-    mut animated: Query<(&mut AnimationCycle, &mut Sprite)>,
+    mut animated: Query<(Entity, &mut AnimationCycle, &mut Sprite)>,
     time: Res<Time>,
     assets: Res<crate::AssetStore>,
     mut commands: Commands,
     loaded_assets: Res<crate::LoadedAssets>,
+    mut animation_events: EventWriter<AnimationEvent>,
+    mut finished: EventWriter<AnimationFinished>,
 ) {
-    let ms_since_last_call = time.delta().as_millis();
-    animated.iter_mut().for_each(|(mut animation, mut sprite)| {
-        animation.timer += ms_since_last_call;
-        if let Some(cycle) = animations.0.get(&animation.animation_tag) {
-            let current_frame = &cycle.frames[animation.current_frame];
-            if animation.timer > current_frame.delay_ms {
-                animation.timer = 0;
+    animated
+        .iter_mut()
+        .for_each(|(entity, mut animation, mut sprite)| {
+            if animation.paused || animation.finished {
+                return;
+            }
+            let elapsed = time.delta().mul_f64(animation.speed as f64);
+            animation.timer += elapsed;
+
+            // A long update may cover several frames. The time left over from
+            // each frame carries into the next, so animations don't drift.
+            let mut steps = 0;
+            loop {
+                let Some(cycle) = animations.0.get(&animation.animation_tag) else {
+                    log::warn!("Animation Cycle [{}] not found!", animation.animation_tag);
+                    break;
+                };
+                animation.restart_if_out_of_range(cycle.frames.len());
+                let Some(current_frame) = cycle.frames.get(animation.current_frame) else {
+                    break;
+                };
+                if animation.timer < current_frame.delay {
+                    break;
+                }
+                // Frames without a delay could otherwise loop forever.
+                if steps == MAX_FRAMES_PER_UPDATE {
+                    animation.timer = Duration::ZERO;
+                    break;
+                }
+                steps += 1;
+
+                animation.timer -= current_frame.delay;
+                let mut moved = false;
                 for action in current_frame.action.iter() {
                     match action {
                         AnimationOption::None => {}
                         AnimationOption::NextFrame => {
-                            animation.current_frame += 1;
+                            // Moving on from the last frame starts again.
+                            animation.current_frame =
+                                (animation.current_frame + 1) % cycle.frames.len();
+                            moved = true;
                         }
                         AnimationOption::GoToFrame(frame) => {
                             animation.current_frame = *frame;
+                            moved = true;
                         }
                         AnimationOption::SwitchToAnimation(new) => {
-                            animation.animation_tag = new.to_string();
-                            animation.current_frame = 0;
+                            let timer = animation.timer;
+                            animation.restart(new.to_string());
+                            animation.timer = timer;
+                            moved = true;
                         }
                         AnimationOption::PlaySound(tag) => {
-                            assets.play(tag, &mut commands, &loaded_assets);
+                            if let Err(error) = assets.try_play(tag, &mut commands, &loaded_assets)
+                            {
+                                log::warn!(
+                                    "Animation Cycle [{}] can't play its sound: {error}",
+                                    animation.animation_tag
+                                );
+                            }
+                        }
+                        AnimationOption::Event(name) => {
+                            animation_events.write(AnimationEvent {
+                                entity,
+                                name: name.clone(),
+                            });
                         }
                     }
-                    if let Some(ta) = &mut sprite.texture_atlas {
-                        ta.index = cycle.frames[animation.current_frame].sprite_index;
-                    }
+                }
+                if !moved && animation.advance(cycle.mode, cycle.frames.len()) {
+                    finished.write(AnimationFinished {
+                        entity,
+                        animation: animation.animation_tag.clone(),
+                    });
+                    break;
                 }
             }
-        } else {
-            log::warn!("Animation Cycle [{}] not found!", animation.animation_tag);
-        }
-    })
+
+            // The frame's actions may have switched to another animation.
+            if let Some(cycle) = animations.0.get(&animation.animation_tag) {
+                animation.restart_if_out_of_range(cycle.frames.len());
+                if let Some(frame) = cycle.frames.get(animation.current_frame)
+                    && let Some(atlas) = &mut sprite.texture_atlas
+                {
+                    atlas.index = frame.sprite_index;
+                }
+            }
+        })
 }
 
 #[macro_export]
 macro_rules! spawn_animated_sprite {
     ($assets: expr, $commands: expr, $index: expr, $x: expr, $y: expr, $z: expr, $animation_name:expr, $($component:expr),*) =>
     {
-        let Some((img, atlas)) = $assets.get_atlas_handle($index) else {panic!()};
+        let (img, atlas) = $assets
+            .try_get_atlas_handle($index)
+            .unwrap_or_else(|error| panic!("{error}"));
         $commands.spawn((
             Sprite::from_atlas_image(img.clone(), TextureAtlas {
                 layout: atlas.clone(),
@@ -136,6 +407,32 @@ macro_rules! spawn_animated_sprite {
     }
 }
 
+/// Like `spawn_animated_sprite!`, but returns `Result<Entity, AssetError>`
+/// instead of panicking if the sprite sheet isn't available.
+#[macro_export]
+macro_rules! try_spawn_animated_sprite {
+    ($assets: expr, $commands: expr, $index: expr, $x: expr, $y: expr, $z: expr, $animation_name:expr, $($component:expr),*) =>
+    {
+        match $assets.try_get_atlas_handle($index) {
+            Ok((img, atlas)) => {
+                let mut entity = $commands.spawn((
+                    Sprite::from_atlas_image(img, TextureAtlas {
+                        layout: atlas,
+                        index: 0
+                    }),
+                    Transform::from_xyz($x, $y, $z),
+                    AnimationCycle::new($animation_name),
+                ));
+                $(
+                    entity.insert($component);
+                )*
+                Ok::<Entity, $crate::AssetError>(entity.id())
+            }
+            Err(error) => Err(error),
+        }
+    }
+}
+
 #[derive(Component)]
 pub struct ContinualParallax {
     image_width: f32,
@@ -174,3 +471,272 @@ pub fn continual_parallax(
             }
         });
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AssetStore;
+    use bevy::time::TimeUpdateStrategy;
+
+    /// `count` frames of 100ms, showing sprites `0..count`.
+    fn frames(
+        count: usize,
+        actions: impl Fn(usize) -> Vec<AnimationOption>,
+    ) -> Vec<AnimationFrame> {
+        (0..count)
+            .map(|i| AnimationFrame::new(i, 100, actions(i)))
+            .collect()
+    }
+
+    fn looping(count: usize) -> PerFrameAnimation {
+        PerFrameAnimation::new(frames(count, |_| vec![])).with_mode(AnimationMode::Loop)
+    }
+
+    /// Each update advances time by `step`.
+    fn app_with_step(animation: PerFrameAnimation, step: Duration) -> (App, Entity) {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+            .insert_resource(TimeUpdateStrategy::ManualDuration(step))
+            .insert_resource(Animations::new().with_animation("test", animation))
+            .add_event::<AnimationEvent>()
+            .add_event::<AnimationFinished>()
+            .add_systems(Update, cycle_animations);
+        // Bevy normally limits updates to 250ms, to survive a debugger pause.
+        app.world_mut()
+            .resource_mut::<Time<Virtual>>()
+            .set_max_delta(Duration::from_secs(10));
+        let store = AssetStore::new(app.world().resource::<AssetServer>().clone());
+        app.insert_resource(store);
+        let entity = app
+            .world_mut()
+            .spawn((
+                AnimationCycle::new("test"),
+                Sprite {
+                    texture_atlas: Some(TextureAtlas::default()),
+                    ..default()
+                },
+            ))
+            .id();
+        // The first update has no elapsed time.
+        app.update();
+        (app, entity)
+    }
+
+    /// Each update advances time by 100ms, the length of each frame.
+    fn app(animation: PerFrameAnimation) -> (App, Entity) {
+        app_with_step(animation, Duration::from_millis(100))
+    }
+
+    fn sprite_indices(app: &mut App, entity: Entity, updates: usize) -> Vec<usize> {
+        (0..updates)
+            .map(|_| {
+                app.update();
+                let sprite = app.world().get::<Sprite>(entity).unwrap();
+                sprite.texture_atlas.as_ref().unwrap().index
+            })
+            .collect()
+    }
+
+    fn events<E: Event + Clone>(app: &App) -> Vec<E> {
+        let events = app.world().resource::<Events<E>>();
+        let mut cursor = events.get_cursor();
+        cursor.read(events).cloned().collect()
+    }
+
+    #[test]
+    fn test_loop() {
+        let (mut app, entity) = app(looping(3));
+        assert_eq!(sprite_indices(&mut app, entity, 5), vec![1, 2, 0, 1, 2]);
+    }
+
+    #[test]
+    fn test_ping_pong() {
+        let animation =
+            PerFrameAnimation::new(frames(3, |_| vec![])).with_mode(AnimationMode::PingPong);
+        let (mut app, entity) = app(animation);
+        assert_eq!(sprite_indices(&mut app, entity, 6), vec![1, 2, 1, 0, 1, 2]);
+    }
+
+    #[test]
+    fn test_once_finishes_and_sends_events() {
+        let animation = PerFrameAnimation::new(frames(3, |i| {
+            if i == 1 {
+                vec![AnimationOption::Event("throw".to_string())]
+            } else {
+                vec![]
+            }
+        }))
+        .with_mode(AnimationMode::Once);
+        let (mut app, entity) = app(animation);
+        assert_eq!(sprite_indices(&mut app, entity, 3), vec![1, 2, 2]);
+        assert!(
+            app.world()
+                .get::<AnimationCycle>(entity)
+                .unwrap()
+                .is_finished()
+        );
+        assert_eq!(
+            events::<AnimationEvent>(&app),
+            vec![AnimationEvent {
+                entity,
+                name: "throw".to_string()
+            }]
+        );
+        assert_eq!(
+            events::<AnimationFinished>(&app),
+            vec![AnimationFinished {
+                entity,
+                animation: "test".to_string()
+            }]
+        );
+        assert_eq!(sprite_indices(&mut app, entity, 1), vec![2]);
+    }
+
+    #[test]
+    fn test_unknown_sound_does_not_stop_the_animation() {
+        let animation = PerFrameAnimation::new(frames(3, |_| {
+            vec![AnimationOption::PlaySound("missing".to_string())]
+        }))
+        .with_mode(AnimationMode::Loop);
+        let (mut app, entity) = app(animation);
+        assert_eq!(sprite_indices(&mut app, entity, 4), vec![1, 2, 0, 1]);
+    }
+
+    #[test]
+    fn test_finished_animation_can_be_replayed() {
+        let animation =
+            PerFrameAnimation::new(frames(3, |_| vec![])).with_mode(AnimationMode::Once);
+        let (mut app, entity) = app(animation);
+        assert_eq!(sprite_indices(&mut app, entity, 4), vec![1, 2, 2, 2]);
+
+        let mut cycle = app.world_mut().get_mut::<AnimationCycle>(entity).unwrap();
+        cycle.switch("test");
+        assert!(!cycle.is_finished());
+        assert_eq!(sprite_indices(&mut app, entity, 4), vec![1, 2, 2, 2]);
+
+        let mut cycle = app.world_mut().get_mut::<AnimationCycle>(entity).unwrap();
+        cycle.replay();
+        assert_eq!(sprite_indices(&mut app, entity, 2), vec![1, 2]);
+    }
+
+    #[test]
+    fn test_speed_is_limited() {
+        let mut cycle = AnimationCycle::new("test");
+        cycle.set_speed(f32::INFINITY);
+        assert_eq!(cycle.speed(), MAX_SPEED);
+        cycle.set_speed(f32::NAN);
+        assert_eq!(cycle.speed(), 0.0);
+        cycle.set_speed(-2.0);
+        assert_eq!(cycle.speed(), 0.0);
+
+        let (mut app, entity) = app(looping(3));
+        app.world_mut()
+            .get_mut::<AnimationCycle>(entity)
+            .unwrap()
+            .set_speed(f32::MAX);
+        sprite_indices(&mut app, entity, 2);
+    }
+
+    #[test]
+    fn test_manual_mode_follows_actions() {
+        let animation = PerFrameAnimation::new(frames(3, |i| {
+            vec![AnimationOption::GoToFrame(if i == 0 { 2 } else { 0 })]
+        }));
+        let (mut app, entity) = app(animation);
+        assert_eq!(sprite_indices(&mut app, entity, 3), vec![2, 0, 2]);
+    }
+
+    #[test]
+    fn test_next_frame_past_the_end_restarts() {
+        let animation = PerFrameAnimation::new(frames(3, |_| vec![AnimationOption::NextFrame]));
+        let (mut app, entity) = app(animation);
+        assert_eq!(sprite_indices(&mut app, entity, 4), vec![1, 2, 0, 1]);
+        let cycle = app.world().get::<AnimationCycle>(entity).unwrap();
+        assert!(!cycle.is_finished());
+    }
+
+    #[test]
+    fn test_pause_and_speed() {
+        let (mut app, entity) = app(looping(3));
+        let mut cycle = app.world_mut().get_mut::<AnimationCycle>(entity).unwrap();
+        cycle.pause();
+        assert_eq!(sprite_indices(&mut app, entity, 2), vec![0, 0]);
+
+        // At half speed, 100ms of game time is 50ms of animation.
+        let mut cycle = app.world_mut().get_mut::<AnimationCycle>(entity).unwrap();
+        cycle.resume();
+        cycle.set_speed(0.5);
+        assert_eq!(sprite_indices(&mut app, entity, 4), vec![0, 1, 1, 2]);
+    }
+
+    #[test]
+    fn test_sub_millisecond_updates_do_not_drift() {
+        // 80 updates of 12.5ms make exactly one second, or ten frames.
+        let (mut app, entity) = app_with_step(looping(4), Duration::from_micros(12_500));
+        let indices = sprite_indices(&mut app, entity, 80);
+        assert_eq!(indices[78], 1);
+        assert_eq!(indices[79], 2);
+        let changes = indices.windows(2).filter(|pair| pair[0] != pair[1]).count();
+        assert_eq!(changes, 10);
+    }
+
+    #[test]
+    fn test_left_over_time_carries_into_the_next_frame() {
+        // Frames end at 100ms, 200ms and 300ms: after the 2nd, 4th and 5th
+        // updates.
+        let (mut app, entity) = app_with_step(looping(3), Duration::from_millis(60));
+        assert_eq!(sprite_indices(&mut app, entity, 5), vec![0, 1, 1, 2, 0]);
+    }
+
+    #[test]
+    fn test_long_updates_skip_frames() {
+        let (mut app, entity) = app_with_step(looping(5), Duration::from_millis(350));
+        // 350ms covers three frames; 700ms covers seven.
+        assert_eq!(sprite_indices(&mut app, entity, 2), vec![3, 2]);
+    }
+
+    #[test]
+    fn test_long_update_finishes_once_animation() {
+        let animation = PerFrameAnimation::new(frames(3, |_| {
+            vec![AnimationOption::Event("step".to_string())]
+        }))
+        .with_mode(AnimationMode::Once);
+        let (mut app, entity) = app_with_step(animation, Duration::from_secs(1));
+        assert_eq!(sprite_indices(&mut app, entity, 1), vec![2]);
+        // Every frame's event is sent, even though they were skipped.
+        assert_eq!(events::<AnimationEvent>(&app).len(), 3);
+        assert_eq!(events::<AnimationFinished>(&app).len(), 1);
+    }
+
+    #[test]
+    fn test_zero_delay_frames_do_not_hang() {
+        let animation = PerFrameAnimation::new(vec![
+            AnimationFrame::new(0, 0, vec![]),
+            AnimationFrame::new(1, 0, vec![]),
+        ])
+        .with_mode(AnimationMode::Loop);
+        let (mut app, entity) = app(animation);
+        sprite_indices(&mut app, entity, 2);
+        let cycle = app.world().get::<AnimationCycle>(entity).unwrap();
+        assert_eq!(cycle.timer, Duration::ZERO);
+    }
+
+    #[test]
+    fn test_switching_animation_keeps_left_over_time() {
+        let animations = Animations::new()
+            .with_animation(
+                "test",
+                PerFrameAnimation::new(vec![AnimationFrame::new(
+                    0,
+                    100,
+                    vec![AnimationOption::SwitchToAnimation("next".to_string())],
+                )]),
+            )
+            .with_animation("next", looping(3));
+        let (mut app, entity) = app_with_step(looping(1), Duration::from_millis(150));
+        app.insert_resource(animations);
+        // At 150ms, "test" has ended and "next" is 50ms in. By 300ms, two
+        // of its frames have ended.
+        assert_eq!(sprite_indices(&mut app, entity, 2), vec![0, 2]);
+    }
+}
@@ -0,0 +1,237 @@
+use bevy::{
+    asset::{AssetEvent, UntypedAssetId},
+    ecs::system::SystemParam,
+    platform::collections::{HashMap, HashSet},
+    prelude::*,
+};
+
+use crate::{AssetStore, LoadedAssets};
+
+/// Sent when a file registered with the [`AssetStore`] changes on disk and
+/// has been reloaded. Contains the asset's tag. Changes the game makes to
+/// an asset itself, for example through `Assets::get_mut`, don't send it.
+/// Requires [`AssetManager::with_hot_reload`](crate::AssetManager::with_hot_reload).
+#[derive(Event, Debug, Clone, PartialEq, Eq)]
+pub struct AssetReloaded(pub String);
+
+#[derive(SystemParam)]
+pub(crate) struct ReloadEvents<'w, 's> {
+    images: EventReader<'w, 's, AssetEvent<Image>>,
+    sounds: EventReader<'w, 's, AssetEvent<AudioSource>>,
+    fonts: EventReader<'w, 's, AssetEvent<Font>>,
+}
+
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn reload_assets(
+    mut events: ReloadEvents,
+    mut loaded: Local<HashSet<UntypedAssetId>>,
+    mut image_sizes: Local<HashMap<AssetId<Image>, UVec2>>,
+    store: Option<ResMut<AssetStore>>,
+    loaded_assets: Res<LoadedAssets>,
+    image_assets: Res<Assets<Image>>,
+    mut texture_atlases: ResMut<Assets<TextureAtlasLayout>>,
+    mut reloaded: EventWriter<AssetReloaded>,
+) {
+    // Image sizes are remembered, so that a sprite sheet's layout can be
+    // fitted to its image when it changes size.
+    let mut modified = Vec::new();
+    let mut resized = HashMap::new();
+    for event in events.images.read() {
+        let reload = file_reload(event, &mut loaded);
+        match event {
+            AssetEvent::LoadedWithDependencies { id } => {
+                let old_size = image_assets
+                    .get(*id)
+                    .and_then(|image| image_sizes.insert(*id, image.size()));
+                if reload.is_none() {
+                    continue;
+                }
+                modified.push(id.untyped());
+                if let Some(old_size) = old_size
+                    && old_size != image_sizes[id]
+                {
+                    resized.insert(*id, image_sizes[id]);
+                }
+            }
+            AssetEvent::Removed { id } => {
+                image_sizes.remove(id);
+            }
+            _ => {}
+        }
+    }
+    modified.extend(
+        events
+            .sounds
+            .read()
+            .filter_map(|event| file_reload(event, &mut loaded))
+            .map(|id| id.untyped()),
+    );
+    modified.extend(
+        events
+            .fonts
+            .read()
+            .filter_map(|event| file_reload(event, &mut loaded))
+            .map(|id| id.untyped()),
+    );
+    let Some(mut store) = store else {
+        return;
+    };
+    if modified.is_empty() {
+        return;
+    }
+
+    let AssetStore {
+        asset_index,
+        atlases_to_build,
+        atlases,
+        ..
+    } = store.as_mut();
+    for (tag, handle) in asset_index.iter() {
+        let Some(loaded) = loaded_assets.get(handle) else {
+            continue;
+        };
+        if !modified.contains(&loaded.handle.id()) {
+            continue;
+        }
+
+        let Some(sheet) = atlases_to_build
+            .iter_mut()
+            .find(|atlas| atlas.texture_tag == *tag)
+        else {
+            reloaded.write(AssetReloaded(tag.clone()));
+            continue;
+        };
+
+        // The layout is replaced in place, so sprites already using it pick
+        // up the change.
+        if let Some((image, layout)) = atlases.get(&sheet.tag)
+            && let Some(new_size) = resized.get(&image.id())
+            && let Some(layout) = texture_atlases.get_mut(layout)
+        {
+            sheet.layout.resize(*new_size);
+            *layout = sheet.layout.build();
+        }
+        reloaded.write(AssetReloaded(sheet.tag.clone()));
+    }
+}
+
+/// Returns the asset's id if `event` shows that its file was reloaded.
+/// Bevy sends `LoadedWithDependencies` each time a file finishes loading,
+/// so seeing it again for the same asset means a reload. `Modified` isn't
+/// used, as it is also sent when the game changes an asset itself.
+pub(crate) fn file_reload<A: Asset>(
+    event: &AssetEvent<A>,
+    loaded: &mut HashSet<UntypedAssetId>,
+) -> Option<AssetId<A>> {
+    match event {
+        AssetEvent::LoadedWithDependencies { id } => (!loaded.insert(id.untyped())).then_some(*id),
+        AssetEvent::Removed { id } => {
+            loaded.remove(&id.untyped());
+            None
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{FutureAtlas, SpriteSheetLayout};
+    use bevy::{
+        asset::LoadedUntypedAsset,
+        render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+    };
+
+    fn image(width: u32, height: u32) -> Image {
+        Image::new_fill(
+            Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            &[0, 0, 0, 255],
+            TextureFormat::Rgba8UnormSrgb,
+            default(),
+        )
+    }
+
+    #[test]
+    fn test_resized_sprite_sheet_rebuilds_layout() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+            .init_asset::<Image>()
+            .init_asset::<AudioSource>()
+            .init_asset::<Font>()
+            .init_asset::<TextureAtlasLayout>()
+            .add_event::<AssetReloaded>()
+            .add_systems(Update, reload_assets);
+
+        let world = app.world_mut();
+        // Four 16x16 sprites, 2 pixels apart, with a 1 pixel margin.
+        let sheet = world.resource_mut::<Assets<Image>>().add(image(72, 18));
+        let loaded = world
+            .resource_mut::<LoadedAssets>()
+            .add(LoadedUntypedAsset {
+                handle: sheet.clone().untyped(),
+            });
+        let sheet_layout = SpriteSheetLayout::Grid {
+            title_size: Vec2::new(16.0, 16.0),
+            sprites_x: 4,
+            sprites_y: 1,
+            padding: Vec2::new(2.0, 0.0),
+            offset: Vec2::new(1.0, 1.0),
+        };
+        let layout = world
+            .resource_mut::<Assets<TextureAtlasLayout>>()
+            .add(sheet_layout.build());
+        let mut store = AssetStore::new(world.resource::<AssetServer>().clone());
+        store.atlases_to_build.push(FutureAtlas {
+            tag: "bats".to_string(),
+            texture_tag: "bats_base".to_string(),
+            layout: sheet_layout,
+        });
+        store.asset_index.insert("bats_base".to_string(), loaded);
+        store
+            .atlases
+            .insert("bats".to_string(), (sheet.clone(), layout.clone()));
+        world.insert_resource(store);
+        // The asset server sends `LoadedWithDependencies` each time it
+        // finishes loading the file.
+        let file_loaded = |app: &mut App| {
+            app.world_mut()
+                .send_event(AssetEvent::LoadedWithDependencies { id: sheet.id() });
+            app.update();
+        };
+        file_loaded(&mut app);
+
+        // Changes made by the game aren't reloads.
+        app.world_mut()
+            .resource_mut::<Assets<Image>>()
+            .get_mut(&sheet)
+            .unwrap();
+        app.update();
+        app.update();
+        assert!(app.world().resource::<Events<AssetReloaded>>().is_empty());
+
+        // An artist adds a fifth sprite.
+        *app.world_mut()
+            .resource_mut::<Assets<Image>>()
+            .get_mut(&sheet)
+            .unwrap() = image(90, 18);
+        file_loaded(&mut app);
+
+        let world = app.world();
+        let layout = world
+            .resource::<Assets<TextureAtlasLayout>>()
+            .get(&layout)
+            .unwrap();
+        assert_eq!(layout.textures.len(), 5);
+        assert_eq!(layout.textures[4], URect::new(73, 1, 89, 17));
+
+        let events = world.resource::<Events<AssetReloaded>>();
+        let mut cursor = events.get_cursor();
+        let reloaded: Vec<&AssetReloaded> = cursor.read(events).collect();
+        assert_eq!(reloaded, vec![&AssetReloaded("bats".to_string())]);
+    }
+}
@@ -1,12 +1,126 @@
 use crate::LoadedAssets;
 use crate::bevy_assets::asset_manager::setup_asset_store;
-use crate::{AssetManager, AssetStore, MenuResource, egui::egui::Window};
+use crate::{
+    AssetGroups, AssetManager, AssetStore, MenuResource,
+    egui::egui::{ProgressBar, Window},
+};
 use bevy::state::state::FreelyMutableState;
-use bevy::{asset::LoadedUntypedAsset, prelude::*};
+use bevy::{
+    asset::{LoadState, LoadedUntypedAsset},
+    ecs::system::SystemParam,
+    platform::time::Instant,
+    prelude::*,
+};
 use bevy_egui::EguiContexts;
 
+/// Sent when an asset fails to load while the game is in its loading state.
+#[derive(Event, Debug, Clone)]
+pub struct AssetLoadFailed {
+    /// The tag the asset was registered with.
+    pub tag: String,
+    /// The file that couldn't be loaded.
+    pub path: String,
+    /// Why loading failed.
+    pub error: String,
+}
+
 #[derive(Resource)]
-pub(crate) struct AssetsToLoad(Vec<Handle<LoadedUntypedAsset>>);
+pub(crate) struct AssetsToLoad {
+    loading: Vec<PendingAsset>,
+    failed: Vec<AssetLoadFailed>,
+    total: usize,
+    total_bytes: u64,
+    loaded_bytes: u64,
+    started: Instant,
+}
+
+impl AssetsToLoad {
+    fn new(loading: Vec<PendingAsset>) -> Self {
+        Self {
+            total: loading.len(),
+            total_bytes: loading.iter().filter_map(|asset| asset.bytes).sum(),
+            loaded_bytes: 0,
+            loading,
+            failed: vec![],
+            started: Instant::now(),
+        }
+    }
+
+    /// Checks on the assets still loading, returning any that have just
+    /// failed.
+    fn poll(&mut self, asset_server: &AssetServer) -> Vec<AssetLoadFailed> {
+        let mut failures = vec![];
+        self.loading.retain(
+            |asset| match asset_server.get_load_state(asset.handle.id()) {
+                Some(LoadState::Loaded) => {
+                    self.loaded_bytes += asset.bytes.unwrap_or(0);
+                    false
+                }
+                Some(LoadState::Failed(error)) => {
+                    failures.push(AssetLoadFailed {
+                        tag: asset.tag.clone(),
+                        path: asset.path.clone(),
+                        error: error.to_string(),
+                    });
+                    false
+                }
+                _ => true,
+            },
+        );
+        self.failed.extend(failures.iter().cloned());
+        failures
+    }
+
+    /// Starts loading every failed asset again.
+    fn retry(&mut self, store: &mut AssetStore) {
+        for failure in self.failed.drain(..) {
+            // Loading a path that previously failed starts a fresh attempt.
+            store.reload(&failure.tag);
+            if let Some(asset) = PendingAsset::new(failure.tag, store) {
+                self.loading.push(asset);
+            }
+        }
+    }
+}
+
+struct PendingAsset {
+    tag: String,
+    path: String,
+    handle: Handle<LoadedUntypedAsset>,
+    bytes: Option<u64>,
+}
+
+impl PendingAsset {
+    /// Waits for an `AssetStore` index tag, if the store knows about it.
+    fn new(tag: String, store: &AssetStore) -> Option<Self> {
+        let handle = store.asset_index.get(&tag)?.clone();
+        // Handles from `load_untyped` report their path under a special
+        // asset source, so use the filename the asset was added with.
+        let path = store.filenames.get(&tag).cloned().unwrap_or_default();
+        let bytes = file_size(&store.file_path, &path);
+        Some(Self {
+            tag,
+            path,
+            handle,
+            bytes,
+        })
+    }
+}
+
+/// The size of an asset's file, if it is read from the local file system.
+/// `asset_folder` is the `AssetPlugin`'s `file_path`.
+#[cfg(not(target_arch = "wasm32"))]
+fn file_size(asset_folder: &str, path: &str) -> Option<u64> {
+    let file = bevy::asset::io::file::FileAssetReader::get_base_path()
+        .join(asset_folder)
+        .join(path);
+    std::fs::metadata(file).ok().map(|metadata| metadata.len())
+}
+
+#[cfg(target_arch = "wasm32")]
+fn file_size(_asset_folder: &str, _path: &str) -> Option<u64> {
+    None
+}
 
 pub(crate) fn setup(
     assets: Option<Res<AssetStore>>,
@@ -23,42 +137,130 @@ pub(crate) fn setup(
         ),
     };
 
-    let assets_to_load = assets
+    let loading = assets
         .asset_index
-        .values()
-        .cloned()
-        .collect::<Vec<Handle<LoadedUntypedAsset>>>();
+        .keys()
+        .filter_map(|tag| PendingAsset::new(tag.clone(), assets))
+        .collect();
+
+    commands.insert_resource(AssetsToLoad::new(loading));
+}
+
+/// Acquires an asset group on entering its loading state, and waits for
+/// the group's assets on the loading screen.
+pub(crate) fn setup_group(
+    group: String,
+) -> impl FnMut(ResMut<AssetGroups>, ResMut<AssetStore>, Commands) {
+    move |mut groups, mut store, mut commands| {
+        groups.acquire(&group, &mut store);
+        let loading = groups
+            .index_tags(&group)
+            .into_iter()
+            .filter_map(|tag| PendingAsset::new(tag, &store))
+            .collect();
+        commands.insert_resource(AssetsToLoad::new(loading));
+    }
+}
+
+pub(crate) fn release_group(group: String) -> impl FnMut(ResMut<AssetGroups>, ResMut<AssetStore>) {
+    move |mut groups, mut store| groups.release(&group, &mut store)
+}
 
-    commands.insert_resource(AssetsToLoad(assets_to_load));
+/// The assets the loading screen waits for, and builds atlases from once
+/// they have loaded.
+#[derive(SystemParam)]
+pub(crate) struct LoadingAssets<'w> {
+    asset_server: Res<'w, AssetServer>,
+    store: ResMut<'w, AssetStore>,
+    texture_atlases: ResMut<'w, Assets<TextureAtlasLayout>>,
+    images: ResMut<'w, Assets<Image>>,
+    loaded_assets: Res<'w, LoadedAssets>,
 }
 
 pub(crate) fn run<T>(
-    asset_server: Res<AssetServer>,
+    mut assets: LoadingAssets,
     mut to_load: ResMut<AssetsToLoad>,
+    current_state: Res<State<T>>,
     mut state: ResMut<NextState<T>>,
     mut egui_context: EguiContexts,
     menu_info: Res<MenuResource<T>>,
-    mut store: ResMut<AssetStore>,
-    mut texture_atlases: ResMut<Assets<TextureAtlasLayout>>,
-    loaded_assets: Res<LoadedAssets>,
+    mut failures: EventWriter<AssetLoadFailed>,
 ) where
     T: States + FromWorld + FreelyMutableState,
 {
-    to_load
-        .0
-        .retain(|handle| match asset_server.get_load_state(handle.id()) {
-            Some(bevy::asset::LoadState::Loaded) => false,
-            _ => true,
-        });
-
-    if to_load.0.is_empty() {
-        load_atlases(&mut store, &mut texture_atlases, &loaded_assets);
-        state.set(menu_info.menu_state.clone());
+    for failure in to_load.poll(&assets.asset_server) {
+        error!("Unable to load {}: {}", failure.path, failure.error);
+        failures.write(failure);
     }
 
+    let loaded = to_load.total - to_load.loading.len() - to_load.failed.len();
+    let progress = if to_load.total_bytes > 0 {
+        to_load.loaded_bytes as f32 / to_load.total_bytes as f32
+    } else if to_load.total > 0 {
+        loaded as f32 / to_load.total as f32
+    } else {
+        1.0
+    };
+    let elapsed = to_load.started.elapsed();
+
+    let mut retry = false;
+    let mut continue_anyway = false;
     Window::new("Loading, Please Wait").show(egui_context.ctx_mut(), |ui| {
-        ui.label(format!("Assets remaining: {}", to_load.0.len()));
+        let mut status = format!("{loaded} of {} assets", to_load.total);
+        if to_load.total_bytes > 0 {
+            status += &format!(
+                ", {} of {} KiB",
+                to_load.loaded_bytes / 1024,
+                to_load.total_bytes / 1024
+            );
+        }
+        ui.add(ProgressBar::new(progress).text(status));
+        ui.label(format!("Elapsed: {:.1}s", elapsed.as_secs_f32()));
+        if !to_load.loading.is_empty() {
+            let mut waiting: Vec<&str> = to_load
+                .loading
+                .iter()
+                .map(|asset| asset.tag.as_str())
+                .collect();
+            waiting.sort();
+            ui.label(format!("Waiting for: {}", waiting.join(", ")));
+        }
+        if !to_load.failed.is_empty() {
+            ui.separator();
+            ui.label("Some assets failed to load:");
+            for failure in to_load.failed.iter() {
+                ui.label(format!("{}: {}", failure.path, failure.error));
+            }
+            ui.horizontal(|ui| {
+                retry = ui.button("Retry").clicked();
+                if to_load.loading.is_empty() {
+                    continue_anyway = ui.button("Continue anyway").clicked();
+                }
+            });
+        }
     });
+
+    if retry {
+        to_load.retry(&mut assets.store);
+    }
+
+    let finished = to_load.failed.is_empty() || continue_anyway;
+    if to_load.loading.is_empty() && finished && elapsed >= menu_info.min_loading_time {
+        load_atlases(
+            &mut assets.store,
+            &mut assets.texture_atlases,
+            &mut assets.images,
+            &assets.loaded_assets,
+        );
+        // Asset groups have their own loading states; the initial load
+        // leads to the main menu.
+        let next_state = menu_info
+            .asset_groups
+            .iter()
+            .find(|group| group.loading_state == *current_state.get())
+            .map_or(&menu_info.menu_state, |group| &group.next_state);
+        state.set(next_state.clone());
+    }
 }
 
 pub(crate) fn exit(mut commands: Commands) {
@@ -68,22 +270,245 @@ pub(crate) fn exit(mut commands: Commands) {
 fn load_atlases(
     store: &mut AssetStore,
     texture_atlases: &mut Assets<TextureAtlasLayout>,
+    images: &mut Assets<Image>,
     loaded_assets: &LoadedAssets,
 ) {
+    for packed in store.packed_to_build.clone() {
+        if store.atlases.contains_key(&packed.tag) {
+            continue;
+        }
+        let handles: Vec<(String, Handle<Image>)> = packed
+            .images
+            .iter()
+            .filter_map(|tag| Some((tag.clone(), store.get_handle(tag, loaded_assets)?)))
+            .collect();
+        let mut builder = TextureAtlasBuilder::default();
+        for (_, handle) in handles.iter() {
+            if let Some(image) = images.get(handle) {
+                builder.add_texture(Some(handle.id()), image);
+            }
+        }
+        let (layout, sources, image) = match builder.build() {
+            Ok(atlas) => atlas,
+            Err(error) => {
+                // The images can still be drawn one at a time.
+                warn!("Unable to pack atlas {}: {error}", packed.tag);
+                continue;
+            }
+        };
+        for (tag, handle) in handles {
+            if let Some(index) = sources.texture_index(&handle) {
+                store.packed_images.insert(tag, (packed.tag.clone(), index));
+            }
+        }
+        let atlas = (images.add(image), texture_atlases.add(layout));
+        store.atlases.insert(packed.tag, atlas);
+    }
+
     for new_atlas in store.atlases_to_build.iter() {
-        let atlas = TextureAtlasLayout::from_grid(
-            new_atlas.title_size.as_uvec2(),
-            new_atlas.sprites_x as u32,
-            new_atlas.sprites_y as u32,
-            None,
-            None,
-        );
+        if store.atlases.contains_key(&new_atlas.tag) {
+            continue;
+        }
+        let atlas = new_atlas.layout.build();
+        // Skip sheets whose image failed to load, if the player chose to
+        // continue anyway.
+        let Some(img) = store.get_handle(&new_atlas.texture_tag, loaded_assets) else {
+            continue;
+        };
         let atlas_handle = texture_atlases.add(atlas);
-        let img = store
-            .get_handle(&new_atlas.texture_tag, loaded_assets)
-            .unwrap();
         store
             .atlases
             .insert(new_atlas.tag.clone(), (img, atlas_handle));
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bevy_assets::asset_manager::AssetType;
+    use bevy::{
+        asset::{AssetLoader, LoadContext, io::Reader},
+        render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+    };
+
+    #[derive(Asset, TypePath)]
+    struct Level;
+
+    #[derive(Default)]
+    struct LevelLoader;
+
+    impl AssetLoader for LevelLoader {
+        type Asset = Level;
+        type Settings = ();
+        type Error = std::io::Error;
+
+        async fn load(
+            &self,
+            _reader: &mut dyn Reader,
+            _settings: &(),
+            _load_context: &mut LoadContext<'_>,
+        ) -> Result<Level, std::io::Error> {
+            Ok(Level)
+        }
+
+        fn extensions(&self) -> &[&str] {
+            &["level"]
+        }
+    }
+
+    fn wait_for(app: &mut App, to_load: &mut AssetsToLoad) -> Vec<AssetLoadFailed> {
+        let mut failures = vec![];
+        for _ in 0..500 {
+            app.update();
+            failures.extend(to_load.poll(app.world().resource::<AssetServer>()));
+            if to_load.loading.is_empty() {
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(5));
+        }
+        failures
+    }
+
+    #[test]
+    fn test_failed_assets_can_be_retried() {
+        let dir = std::env::temp_dir().join(format!("my_library_retry_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("one.level");
+        let _ = std::fs::remove_file(&file);
+
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            AssetPlugin {
+                file_path: dir.to_string_lossy().to_string(),
+                ..default()
+            },
+        ))
+        .init_asset::<Level>()
+        .register_asset_loader(LevelLoader);
+        let mut store = AssetStore::new(app.world().resource::<AssetServer>().clone());
+        store.load("level", "one.level", &AssetType::Image);
+        let mut to_load = AssetsToLoad::new(vec![
+            PendingAsset::new("level".to_string(), &store).unwrap(),
+        ]);
+
+        let failures = wait_for(&mut app, &mut to_load);
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].tag, "level");
+        assert_eq!(failures[0].path, "one.level");
+
+        std::fs::write(&file, "").unwrap();
+        to_load.retry(&mut store);
+        let failures = wait_for(&mut app, &mut to_load);
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(failures.is_empty());
+        assert!(to_load.failed.is_empty());
+        assert!(
+            store
+                .try_get_handle::<Level>("level", app.world().resource::<LoadedAssets>())
+                .is_ok()
+        );
+    }
+
+    #[derive(States, Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+    enum TestState {
+        #[default]
+        Loading,
+        LoadingLevel,
+    }
+
+    #[test]
+    fn test_group_is_acquired_when_entering_its_loading_state() {
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            AssetPlugin::default(),
+            bevy::state::app::StatesPlugin,
+        ))
+        .init_state::<TestState>();
+        let mut manager = AssetManager::new().group("level");
+        manager.push_asset("boss".to_string(), "boss.png".to_string(), AssetType::Image);
+        app.insert_resource(manager)
+            .add_systems(OnEnter(TestState::Loading), setup)
+            .add_systems(
+                OnEnter(TestState::LoadingLevel),
+                setup_group("level".to_string()),
+            );
+
+        app.update();
+        assert_eq!(app.world().resource::<AssetGroups>().holders("level"), 0);
+
+        app.world_mut()
+            .resource_mut::<NextState<TestState>>()
+            .set(TestState::LoadingLevel);
+        app.update();
+        assert_eq!(app.world().resource::<AssetGroups>().holders("level"), 1);
+        assert!(
+            app.world()
+                .resource::<AssetStore>()
+                .asset_index
+                .contains_key("boss")
+        );
+        assert_eq!(app.world().resource::<AssetsToLoad>().total, 1);
+    }
+
+    #[test]
+    fn test_file_size_uses_the_asset_folder() {
+        let dir = std::env::temp_dir().join(format!("my_library_size_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("five.level"), "12345").unwrap();
+        let size = file_size(&dir.to_string_lossy(), "five.level");
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(size, Some(5));
+        assert_eq!(file_size("assets", "five.level"), None);
+    }
+
+    #[test]
+    fn test_packed_atlas() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()));
+        let mut store = AssetStore::new(app.world().resource::<AssetServer>().clone());
+        let mut images = Assets::<Image>::default();
+        let mut loaded_assets = LoadedAssets::default();
+        for (tag, width) in [("fuel", 8), ("battery", 16)] {
+            let image = images.add(Image::new_fill(
+                Extent3d {
+                    width,
+                    height: 8,
+                    depth_or_array_layers: 1,
+                },
+                TextureDimension::D2,
+                &[255, 255, 255, 255],
+                TextureFormat::Rgba8UnormSrgb,
+                default(),
+            ));
+            let loaded = loaded_assets.add(LoadedUntypedAsset {
+                handle: image.untyped(),
+            });
+            store.asset_index.insert(tag.to_string(), loaded);
+        }
+        store.load(
+            "pickups",
+            "",
+            &crate::bevy_assets::asset_manager::AssetType::PackedAtlas(vec![
+                "fuel".to_string(),
+                "battery".to_string(),
+            ]),
+        );
+
+        let mut texture_atlases = Assets::<TextureAtlasLayout>::default();
+        load_atlases(
+            &mut store,
+            &mut texture_atlases,
+            &mut images,
+            &loaded_assets,
+        );
+
+        let sprite = store.get_sprite("battery", &loaded_assets).unwrap();
+        let atlas = sprite.texture_atlas.unwrap();
+        let layout = texture_atlases.get(&atlas.layout).unwrap();
+        assert_eq!(layout.textures.len(), 2);
+        assert_eq!(layout.textures[atlas.index].size(), UVec2::new(16, 8));
+        assert_eq!(sprite.image, store.atlases["pickups"].0);
+    }
+}
@@ -1,9 +1,26 @@
-mod asset_manager;
+pub(crate) mod asset_manager;
 pub use asset_manager::AssetManager;
 
+#[cfg(feature = "serde")]
+mod asset_manifest;
+
+#[cfg(feature = "serde")]
+mod sprite_atlas;
+
+#[cfg(feature = "serde")]
+mod animation_file;
+#[cfg(feature = "serde")]
+pub use animation_file::AnimationSet;
+
+mod asset_groups;
+pub use asset_groups::AssetGroups;
+
 mod asset_store;
 pub use asset_store::*;
 
+mod hot_reload;
+pub use hot_reload::AssetReloaded;
+
 mod loading_menu;
 pub use loading_menu::*;
 
@@ -15,7 +32,74 @@ macro_rules! spawn_image {
     ($assets:expr, $commands:expr, $index:expr, $x:expr, $y:expr, $z: expr, $resource:expr, $($component:expr),*) => {
         {
             $commands.spawn((
-                Sprite::from_image($assets.get_handle($index, $resource).unwrap()),
+                $assets
+                    .try_get_sprite($index, $resource)
+                    .unwrap_or_else(|error| panic!("{error}")),
+                Transform::from_xyz($x, $y, $z),
+            ))
+            $(
+                .insert($component)
+            )*
+        }
+    };
+}
+
+/// Like `spawn_image!`, but returns `Result<Entity, AssetError>` instead of
+/// panicking if the image isn't available.
+#[macro_export]
+macro_rules! try_spawn_image {
+    ($assets:expr, $commands:expr, $index:expr, $x:expr, $y:expr, $z: expr, $resource:expr, $($component:expr),*) => {
+        match $assets.try_get_sprite($index, $resource) {
+            Ok(sprite) => {
+                let mut entity = $commands.spawn((
+                    sprite,
+                    Transform::from_xyz($x, $y, $z),
+                ));
+                $(
+                    entity.insert($component);
+                )*
+                Ok::<Entity, $crate::AssetError>(entity.id())
+            }
+            Err(error) => Err(error),
+        }
+    };
+}
+
+/// Spawns a `Text2d` in the world, using a font added with
+/// `AssetManager::add_font`.
+///
+/// ```
+/// use bevy::prelude::*;
+/// use my_library::*;
+///
+/// fn label_base(assets: Res<AssetStore>, loaded_assets: AssetResource, mut commands: Commands) {
+///     spawn_text!(
+///         assets,
+///         commands,
+///         "Base",
+///         "hud",
+///         12.0,
+///         0.0,
+///         40.0,
+///         1.0,
+///         &loaded_assets,
+///         Name::new("base label")
+///     );
+/// }
+/// ```
+#[macro_export]
+macro_rules! spawn_text {
+    ($assets:expr, $commands:expr, $text:expr, $font:expr, $font_size:expr, $x:expr, $y:expr, $z: expr, $resource:expr, $($component:expr),*) => {
+        {
+            $commands.spawn((
+                Text2d::new($text),
+                TextFont {
+                    font: $assets
+                        .try_get_handle($font, $resource)
+                        .unwrap_or_else(|error| panic!("{error}")),
+                    font_size: $font_size,
+                    ..default()
+                },
                 Transform::from_xyz($x, $y, $z),
             ))
             $(
@@ -0,0 +1,291 @@
+use std::fmt;
+
+use bevy::{
+    asset::{AssetLoader, LoadContext, UntypedAssetId, io::Reader},
+    platform::collections::{HashMap, HashSet},
+    prelude::*,
+};
+use serde::{
+    Deserialize, Deserializer,
+    de::{MapAccess, Visitor},
+};
+
+use super::hot_reload::file_reload;
+use crate::{AssetReloaded, AssetStore, SpriteSheetLayout};
+
+/// The JSON written by TexturePacker and Aseprite. Only the parts needed
+/// to find each frame are read.
+#[derive(Deserialize)]
+struct SpriteAtlasFile {
+    frames: Frames,
+    meta: Option<Meta>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Frames {
+    Array(Vec<NamedFrame>),
+    Hash(OrderedFrames),
+}
+
+#[derive(Deserialize)]
+struct NamedFrame {
+    filename: String,
+    #[serde(flatten)]
+    frame: Frame,
+}
+
+#[derive(Deserialize)]
+struct Frame {
+    frame: Rect,
+    #[serde(default)]
+    rotated: bool,
+}
+
+#[derive(Deserialize)]
+struct Rect {
+    x: u32,
+    y: u32,
+    w: u32,
+    h: u32,
+}
+
+#[derive(Deserialize)]
+struct Meta {
+    size: Option<Size>,
+}
+
+#[derive(Deserialize)]
+struct Size {
+    w: u32,
+    h: u32,
+}
+
+/// "Hash" exports list frames as a JSON object. Frame indices follow the
+/// order in the file, so the object is read in order rather than into a
+/// (sorted) map.
+struct OrderedFrames(Vec<(String, Frame)>);
+
+impl<'de> Deserialize<'de> for OrderedFrames {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct FramesVisitor;
+
+        impl<'de> Visitor<'de> for FramesVisitor {
+            type Value = OrderedFrames;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "a map of frame names to frames")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+                let mut frames = Vec::new();
+                while let Some(entry) = map.next_entry()? {
+                    frames.push(entry);
+                }
+                Ok(OrderedFrames(frames))
+            }
+        }
+
+        deserializer.deserialize_map(FramesVisitor)
+    }
+}
+
+/// Reads the frames of the sprite atlas image described by `json`, which was
+/// read from `file`.
+pub(crate) fn parse_frames(file: &str, json: &str) -> anyhow::Result<SpriteSheetLayout> {
+    let atlas: SpriteAtlasFile = serde_json::from_str(json)?;
+    let frames = match atlas.frames {
+        Frames::Array(frames) => frames
+            .into_iter()
+            .map(|named| (named.filename, named.frame))
+            .collect(),
+        Frames::Hash(OrderedFrames(frames)) => frames,
+    };
+
+    let recorded_size = atlas
+        .meta
+        .and_then(|meta| meta.size)
+        .map(|Size { w, h }| UVec2::new(w, h));
+    let mut layout_frames = Vec::with_capacity(frames.len());
+    for (name, frame) in frames {
+        if frame.rotated {
+            anyhow::bail!("frame '{name}' is rotated, which isn't supported");
+        }
+        let Rect { x, y, w, h } = frame.frame;
+        let (Some(right), Some(bottom)) = (x.checked_add(w), y.checked_add(h)) else {
+            anyhow::bail!("frame '{name}' extends beyond the largest possible image");
+        };
+        if let Some(size) = recorded_size
+            && (right > size.x || bottom > size.y)
+        {
+            anyhow::bail!(
+                "frame '{name}' extends beyond the {}x{} image",
+                size.x,
+                size.y
+            );
+        }
+        layout_frames.push((name, URect::new(x, y, right, bottom)));
+    }
+
+    // Without a recorded size, the image is assumed to end at the last frame.
+    let size = recorded_size.unwrap_or_else(|| {
+        layout_frames
+            .iter()
+            .fold(UVec2::ZERO, |size, (_, frame)| size.max(frame.max))
+    });
+
+    Ok(SpriteSheetLayout::Frames {
+        size,
+        frames: layout_frames,
+        file: file.to_string(),
+    })
+}
+
+/// A sprite atlas's JSON file, loaded through the asset server so that
+/// hot reloading notices when it changes.
+#[derive(Asset, TypePath)]
+pub(crate) struct SpriteAtlasFrames(SpriteSheetLayout);
+
+/// Frame files are loaded by type, as their extension is just `.json`.
+#[derive(Default)]
+pub(crate) struct SpriteAtlasFramesLoader;
+
+impl AssetLoader for SpriteAtlasFramesLoader {
+    type Asset = SpriteAtlasFrames;
+    type Settings = ();
+    type Error = anyhow::Error;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<SpriteAtlasFrames, anyhow::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let file = load_context.path().to_string_lossy();
+        let json = std::str::from_utf8(&bytes)?;
+        Ok(SpriteAtlasFrames(parse_frames(&file, json)?))
+    }
+}
+
+/// Watches the JSON files of the sprite atlases in the [`AssetStore`], and
+/// replaces an atlas's layout when its file is reloaded.
+pub(crate) fn reload_frames(
+    mut events: EventReader<AssetEvent<SpriteAtlasFrames>>,
+    mut loaded: Local<HashSet<UntypedAssetId>>,
+    mut watched: Local<HashMap<String, Handle<SpriteAtlasFrames>>>,
+    store: Option<ResMut<AssetStore>>,
+    frame_files: Res<Assets<SpriteAtlasFrames>>,
+    mut texture_atlases: ResMut<Assets<TextureAtlasLayout>>,
+    mut reloaded: EventWriter<AssetReloaded>,
+) {
+    let modified: Vec<AssetId<SpriteAtlasFrames>> = events
+        .read()
+        .filter_map(|event| file_reload(event, &mut loaded))
+        .collect();
+    let Some(mut store) = store else {
+        return;
+    };
+
+    let AssetStore {
+        atlases_to_build,
+        atlases,
+        asset_server,
+        ..
+    } = store.as_mut();
+    // Stop watching atlases whose asset group was released.
+    watched.retain(|tag, _| atlases_to_build.iter().any(|atlas| atlas.tag == *tag));
+    for sheet in atlases_to_build.iter_mut() {
+        let SpriteSheetLayout::Frames { file, .. } = &sheet.layout else {
+            continue;
+        };
+        let frames = watched
+            .entry(sheet.tag.clone())
+            .or_insert_with(|| asset_server.load(file.clone()));
+        if !modified.contains(&frames.id()) {
+            continue;
+        }
+        let Some(SpriteAtlasFrames(layout)) = frame_files.get(&*frames) else {
+            continue;
+        };
+
+        // As with resized sheets, the layout is replaced in place.
+        sheet.layout = layout.clone();
+        if let Some((_, layout)) = atlases.get(&sheet.tag)
+            && let Some(layout) = texture_atlases.get_mut(layout)
+        {
+            *layout = sheet.layout.build();
+        }
+        reloaded.write(AssetReloaded(sheet.tag.clone()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hash_format_keeps_file_order() {
+        let json = r#"{
+            "frames": {
+                "walk 9.aseprite": { "frame": { "x": 0, "y": 0, "w": 16, "h": 24 }, "duration": 100 },
+                "walk 10.aseprite": { "frame": { "x": 16, "y": 0, "w": 20, "h": 24 }, "duration": 100 }
+            },
+            "meta": { "image": "hero.png", "size": { "w": 64, "h": 32 } }
+        }"#;
+        let layout = parse_frames("hero.json", json).unwrap();
+        assert_eq!(layout.frame_index("walk 9.aseprite"), Some(0));
+        assert_eq!(layout.frame_index("walk 10.aseprite"), Some(1));
+        assert_eq!(layout.frame_index("run 1.aseprite"), None);
+
+        let atlas = layout.build();
+        assert_eq!(atlas.size, UVec2::new(64, 32));
+        assert_eq!(atlas.textures[1], URect::new(16, 0, 36, 24));
+    }
+
+    #[test]
+    fn test_array_format() {
+        let json = r#"{
+            "frames": [
+                { "filename": "idle", "frame": { "x": 0, "y": 0, "w": 8, "h": 8 }, "rotated": false },
+                { "filename": "jump", "frame": { "x": 8, "y": 4, "w": 8, "h": 12 }, "rotated": false }
+            ]
+        }"#;
+        let layout = parse_frames("hero.json", json).unwrap();
+        assert_eq!(layout.frame_index("jump"), Some(1));
+        assert_eq!(layout.build().size, UVec2::new(16, 16));
+    }
+
+    #[test]
+    fn test_rotated_frames_are_rejected() {
+        let json = r#"{ "frames": [
+            { "filename": "idle", "frame": { "x": 0, "y": 0, "w": 8, "h": 8 }, "rotated": true }
+        ] }"#;
+        let error = parse_frames("hero.json", json).err().unwrap().to_string();
+        assert!(error.contains("idle"));
+    }
+
+    #[test]
+    fn test_oversized_frames_are_rejected() {
+        let json = r#"{ "frames": [
+            { "filename": "huge", "frame": { "x": 4294967295, "y": 0, "w": 8, "h": 8 } }
+        ] }"#;
+        let error = parse_frames("hero.json", json).err().unwrap().to_string();
+        assert!(error.contains("huge"));
+    }
+
+    #[test]
+    fn test_frames_outside_the_image_are_rejected() {
+        let json = r#"{
+            "frames": [
+                { "filename": "idle", "frame": { "x": 0, "y": 0, "w": 32, "h": 32 } },
+                { "filename": "jump", "frame": { "x": 32, "y": 0, "w": 32, "h": 40 } }
+            ],
+            "meta": { "size": { "w": 64, "h": 32 } }
+        }"#;
+        let error = parse_frames("hero.json", json).err().unwrap().to_string();
+        assert!(error.contains("jump"));
+        assert!(error.contains("64x32"));
+    }
+}
@@ -0,0 +1,380 @@
+use std::time::Duration;
+
+use bevy::{
+    audio::Volume, platform::collections::HashMap, prelude::*,
+    transform::TransformSystem::TransformPropagate,
+};
+
+use super::positional_audio::{start_positional_sounds, update_positional_sounds};
+use crate::{AssetError, AssetStore, LoadedAssets};
+
+/// The channel a sound plays on. Each channel has its own volume.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AudioChannel {
+    /// Background music, see [`GameAudio::play_music`].
+    Music,
+    /// Sound effects. `AssetStore::play` uses this channel.
+    Sfx,
+    /// Menu and interface sounds.
+    Ui,
+}
+
+impl AudioChannel {
+    fn index(self) -> usize {
+        match self {
+            AudioChannel::Music => 0,
+            AudioChannel::Sfx => 1,
+            AudioChannel::Ui => 2,
+        }
+    }
+}
+
+/// Marks an entity playing a sound, with the sound's tag.
+#[derive(Component, Clone, Debug, PartialEq, Eq)]
+pub struct SoundTag(pub String);
+
+/// A background music track, fading in or out.
+#[derive(Component)]
+pub(crate) struct MusicTrack {
+    tag: String,
+    gain: f32,
+    fading_out: bool,
+}
+
+/// `GameAudio` controls how sounds played through the [`AssetStore`] are
+/// mixed: the volume of each [`AudioChannel`], how many copies of a sound
+/// may play at once, and the background music.
+///
+/// Add it as a plugin to configure the starting settings; it is then
+/// available as a resource (`ResMut<GameAudio>`) to change them while the
+/// game runs. Finished sounds are always despawned.
+///
+/// ## Example
+///
+/// ```no_run
+/// use bevy::prelude::*;
+/// use my_library::{AudioChannel, GameAudio};
+/// App::new()
+///     .add_plugins(
+///         GameAudio::new()
+///             .with_volume(AudioChannel::Music, 0.5)
+///             .with_max_instances("flap", 1),
+///     )
+///     .run();
+/// ```
+#[derive(Resource, Clone, Debug)]
+pub struct GameAudio {
+    volumes: [f32; 3],
+    max_instances: HashMap<String, usize>,
+    music: Option<String>,
+    music_fade: Duration,
+}
+
+impl Default for GameAudio {
+    fn default() -> Self {
+        Self {
+            volumes: [1.0; 3],
+            max_instances: HashMap::new(),
+            music: None,
+            music_fade: Duration::from_secs(1),
+        }
+    }
+}
+
+impl GameAudio {
+    /// Creates a `GameAudio` with every channel at full volume, no limit on
+    /// sound instances and a one second music cross-fade.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the starting volume of a channel.
+    pub fn with_volume(mut self, channel: AudioChannel, volume: f32) -> Self {
+        self.set_volume(channel, volume);
+        self
+    }
+
+    /// Limits how many copies of the sound `tag` can play at once. Extra
+    /// requests are dropped while the limit is reached.
+    pub fn with_max_instances<S: ToString>(mut self, tag: S, max: usize) -> Self {
+        self.max_instances.insert(tag.to_string(), max);
+        self
+    }
+
+    /// Sets how long music takes to cross-fade into the next track.
+    pub fn with_music_fade(mut self, duration: Duration) -> Self {
+        self.music_fade = duration;
+        self
+    }
+
+    /// Returns a channel's volume, where `1.0` is the sound's own volume.
+    pub fn volume(&self, channel: AudioChannel) -> f32 {
+        self.volumes[channel.index()]
+    }
+
+    /// Changes a channel's volume, including for sounds already playing.
+    pub fn set_volume(&mut self, channel: AudioChannel, volume: f32) {
+        self.volumes[channel.index()] = volume.max(0.0);
+    }
+
+    /// Starts looping the sound `tag` as background music, cross-fading
+    /// from whatever was playing. Does nothing if `tag` is already playing.
+    pub fn play_music<S: ToString>(&mut self, tag: S) {
+        self.music = Some(tag.to_string());
+    }
+
+    /// Fades out the background music.
+    pub fn stop_music(&mut self) {
+        self.music = None;
+    }
+
+    /// The tag of the background music that is playing (or fading in).
+    pub fn music(&self) -> Option<&str> {
+        self.music.as_deref()
+    }
+}
+
+impl Plugin for GameAudio {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(self.clone());
+        app.add_systems(Update, update_music);
+        // Bevy starts new sounds after transform propagation, so settings
+        // applied before it are used from the first sample.
+        app.add_systems(
+            PostUpdate,
+            (
+                throttle_sounds,
+                apply_channel_volumes,
+                start_positional_sounds,
+            )
+                .chain()
+                .before(TransformPropagate),
+        );
+        // Sinks are added as Bevy starts sounds, after transform propagation.
+        app.add_systems(Last, update_positional_sounds);
+    }
+}
+
+/// Returns a closure system that switches to the music `tag`.
+pub(crate) fn play_state_music(tag: String) -> impl FnMut(Option<ResMut<GameAudio>>) {
+    move |audio| match audio {
+        Some(mut audio) => audio.play_music(&tag),
+        None => warn!("Music '{tag}' needs the GameAudio plugin"),
+    }
+}
+
+fn throttle_sounds(
+    audio: Res<GameAudio>,
+    sounds: Query<(Entity, Ref<SoundTag>)>,
+    mut commands: Commands,
+) {
+    if audio.max_instances.is_empty() {
+        return;
+    }
+    let mut playing: HashMap<String, usize> = HashMap::new();
+    for (_, tag) in sounds.iter().filter(|(_, tag)| !tag.is_added()) {
+        *playing.entry(tag.0.clone()).or_default() += 1;
+    }
+    for (entity, tag) in sounds.iter().filter(|(_, tag)| tag.is_added()) {
+        let Some(max) = audio.max_instances.get(&tag.0) else {
+            continue;
+        };
+        let count = playing.entry(tag.0.clone()).or_default();
+        if *count >= *max {
+            commands.entity(entity).despawn();
+        } else {
+            *count += 1;
+        }
+    }
+}
+
+fn apply_channel_volumes(
+    audio: Res<GameAudio>,
+    mut new_sounds: Query<(&AudioChannel, &mut PlaybackSettings), Added<AudioChannel>>,
+    mut sinks: Query<(&AudioChannel, &mut AudioSink), Without<MusicTrack>>,
+) {
+    for (channel, mut settings) in new_sounds.iter_mut() {
+        settings.volume = Volume::Linear(audio.volume(*channel));
+    }
+    if audio.is_changed() {
+        for (channel, mut sink) in sinks.iter_mut() {
+            sink.set_volume(Volume::Linear(audio.volume(*channel)));
+        }
+    }
+}
+
+fn update_music(
+    mut audio: ResMut<GameAudio>,
+    store: Option<Res<AssetStore>>,
+    loaded_assets: Res<LoadedAssets>,
+    time: Res<Time>,
+    mut tracks: Query<(Entity, &mut MusicTrack, Option<&mut AudioSink>)>,
+    mut commands: Commands,
+) {
+    let playing = tracks
+        .iter()
+        .any(|(_, track, _)| !track.fading_out && audio.music.as_ref() == Some(&track.tag));
+    if !playing {
+        for (_, mut track, _) in tracks.iter_mut() {
+            track.fading_out = true;
+        }
+        if let Some(tag) = audio.music.clone()
+            && let Some(store) = store
+        {
+            match store.try_get_handle::<AudioSource>(&tag, &loaded_assets) {
+                Ok(handle) => {
+                    commands.spawn((
+                        AudioPlayer::new(handle),
+                        PlaybackSettings::LOOP.with_volume(Volume::Linear(0.0)),
+                        MusicTrack {
+                            tag,
+                            gain: 0.0,
+                            fading_out: false,
+                        },
+                    ));
+                }
+                // Music in an asset group may still be loading.
+                Err(AssetError::Loading { .. }) => {}
+                Err(error) => {
+                    warn!("Unable to play music: {error}");
+                    audio.music = None;
+                }
+            }
+        }
+    }
+
+    let step = if audio.music_fade.is_zero() {
+        1.0
+    } else {
+        time.delta_secs() / audio.music_fade.as_secs_f32()
+    };
+    for (entity, mut track, sink) in tracks.iter_mut() {
+        track.gain = if track.fading_out {
+            track.gain - step
+        } else {
+            track.gain + step
+        }
+        .clamp(0.0, 1.0);
+        if track.fading_out && track.gain == 0.0 {
+            commands.entity(entity).despawn();
+        } else if let Some(mut sink) = sink {
+            sink.set_volume(Volume::Linear(
+                track.gain * audio.volume(AudioChannel::Music),
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::{asset::LoadedUntypedAsset, time::TimeUpdateStrategy};
+
+    fn app(audio: GameAudio) -> App {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default(), audio))
+            .init_asset::<AudioSource>();
+        app
+    }
+
+    fn spawn_sound(app: &mut App, tag: &str, channel: AudioChannel) -> Entity {
+        app.world_mut()
+            .spawn((
+                PlaybackSettings::DESPAWN,
+                channel,
+                SoundTag(tag.to_string()),
+            ))
+            .id()
+    }
+
+    #[test]
+    fn test_sounds_are_throttled() {
+        let mut app = app(GameAudio::new().with_max_instances("flap", 2));
+        spawn_sound(&mut app, "flap", AudioChannel::Sfx);
+        app.update();
+        for _ in 0..3 {
+            spawn_sound(&mut app, "flap", AudioChannel::Sfx);
+            spawn_sound(&mut app, "crash", AudioChannel::Sfx);
+        }
+        app.update();
+
+        let world = app.world_mut();
+        let mut sounds = world.query::<&SoundTag>();
+        let mut count = |tag: &str| sounds.iter(world).filter(|t| t.0 == tag).count();
+        assert_eq!(count("flap"), 2);
+        assert_eq!(count("crash"), 3);
+    }
+
+    #[test]
+    fn test_new_sounds_use_channel_volume() {
+        let mut app = app(GameAudio::new().with_volume(AudioChannel::Ui, 0.25));
+        let click = spawn_sound(&mut app, "click", AudioChannel::Ui);
+        let flap = spawn_sound(&mut app, "flap", AudioChannel::Sfx);
+        app.update();
+
+        let world = app.world();
+        let volume = |entity| world.get::<PlaybackSettings>(entity).unwrap().volume;
+        assert_eq!(volume(click), Volume::Linear(0.25));
+        assert_eq!(volume(flap), Volume::Linear(1.0));
+    }
+
+    #[test]
+    fn test_music_cross_fades() {
+        let mut app = app(GameAudio::new().with_music_fade(Duration::from_millis(500)));
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+            100,
+        )));
+        let world = app.world_mut();
+        let mut store = AssetStore::new(world.resource::<AssetServer>().clone());
+        for tag in ["menu", "level"] {
+            let source = world
+                .resource_mut::<Assets<AudioSource>>()
+                .add(AudioSource {
+                    bytes: Vec::new().into(),
+                });
+            let loaded = world
+                .resource_mut::<LoadedAssets>()
+                .add(LoadedUntypedAsset {
+                    handle: source.untyped(),
+                });
+            store.asset_index.insert(tag.to_string(), loaded);
+        }
+        world.insert_resource(store);
+
+        let gains = |app: &mut App| {
+            let world = app.world_mut();
+            let mut tracks: Vec<(String, f32)> = world
+                .query::<&MusicTrack>()
+                .iter(world)
+                .map(|track| (track.tag.clone(), track.gain))
+                .collect();
+            tracks.sort_by(|a, b| a.0.cmp(&b.0));
+            tracks
+        };
+
+        app.world_mut()
+            .resource_mut::<GameAudio>()
+            .play_music("menu");
+        for _ in 0..8 {
+            app.update();
+        }
+        assert_eq!(gains(&mut app), vec![("menu".to_string(), 1.0)]);
+
+        app.world_mut()
+            .resource_mut::<GameAudio>()
+            .play_music("level");
+        for _ in 0..3 {
+            app.update();
+        }
+        let tracks = gains(&mut app);
+        assert_eq!(tracks.len(), 2);
+        assert_eq!(tracks[0].0, "level");
+        assert_eq!(tracks[1].0, "menu");
+        assert!(tracks[0].1 > 0.0 && tracks[1].1 < 1.0);
+
+        for _ in 0..8 {
+            app.update();
+        }
+        assert_eq!(gains(&mut app), vec![("level".to_string(), 1.0)]);
+    }
+}
@@ -0,0 +1,5 @@
+mod game_audio;
+pub use game_audio::*;
+
+mod positional_audio;
+pub use positional_audio::*;
@@ -0,0 +1,245 @@
+use bevy::{
+    audio::{SpatialScale, Volume},
+    prelude::*,
+    transform::helper::TransformHelper,
+};
+
+use crate::{AudioChannel, GameAudio};
+
+/// Marks the entity that hears positional sounds, usually the camera.
+/// Only the first listener found is used.
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct SoundListener;
+
+/// How a positional sound gets quieter with distance.
+#[derive(Clone, Copy, Debug)]
+pub enum Falloff {
+    /// The volume doesn't change with distance.
+    None,
+    /// The volume drops evenly from `min_distance` to `max_distance`.
+    Linear,
+    /// The volume halves each time the distance doubles, as with real
+    /// sound. Sounds beyond `max_distance` are silent.
+    InverseDistance,
+    /// The volume drops quickly at first and then tails off.
+    Quadratic,
+    /// A custom curve, mapping the fraction of the way from `min_distance`
+    /// to `max_distance` (`0.0..=1.0`) to a volume.
+    Custom(fn(f32) -> f32),
+}
+
+/// Makes a sound positional: it is attenuated and panned according to
+/// where it is relative to the [`SoundListener`]. The sound plays from the
+/// entity's `GlobalTransform`, so a sound spawned as a child of a moving
+/// entity follows it.
+///
+/// `AssetStore::play_at` and `AssetStore::play_from` add it for you.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct SoundEmitter {
+    /// How the volume drops with distance.
+    pub falloff: Falloff,
+    /// Sounds closer than this play at full volume.
+    pub min_distance: f32,
+    /// Sounds further than this are silent, and sounds this far to the
+    /// side are panned fully to one ear.
+    pub max_distance: f32,
+}
+
+impl Default for SoundEmitter {
+    fn default() -> Self {
+        Self {
+            falloff: Falloff::InverseDistance,
+            min_distance: 100.0,
+            max_distance: 1000.0,
+        }
+    }
+}
+
+impl SoundEmitter {
+    /// Creates an emitter with the default inverse distance falloff,
+    /// audible from 100 to 1000 units away. In a 2D game with one unit per
+    /// pixel and 24 pixel tiles, sounds are at full volume within about
+    /// four tiles and silent beyond about forty. Use `with_range` for
+    /// other scales.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Uses a different falloff curve.
+    pub fn with_falloff(mut self, falloff: Falloff) -> Self {
+        self.falloff = falloff;
+        self
+    }
+
+    /// Sets the distances over which the sound fades out.
+    pub fn with_range(mut self, min_distance: f32, max_distance: f32) -> Self {
+        self.min_distance = min_distance;
+        self.max_distance = max_distance.max(min_distance);
+        self
+    }
+
+    /// The volume of the sound at `distance` from the listener, from `0.0`
+    /// to `1.0`.
+    pub fn gain(&self, distance: f32) -> f32 {
+        if distance <= self.min_distance {
+            return 1.0;
+        }
+        if distance >= self.max_distance {
+            return if matches!(self.falloff, Falloff::None) {
+                1.0
+            } else {
+                0.0
+            };
+        }
+        let t = (distance - self.min_distance) / (self.max_distance - self.min_distance);
+        let gain = match self.falloff {
+            Falloff::None => 1.0,
+            Falloff::Linear => 1.0 - t,
+            Falloff::InverseDistance => self.min_distance.max(1.0) / distance,
+            Falloff::Quadratic => (1.0 - t) * (1.0 - t),
+            Falloff::Custom(curve) => curve(t),
+        };
+        gain.clamp(0.0, 1.0)
+    }
+
+    /// Where the sound sits between the left (`-1.0`) and right (`1.0`)
+    /// ears, for a sound `offset` from the listener.
+    pub fn pan(&self, offset: Vec2) -> f32 {
+        if self.max_distance <= 0.0 {
+            return 0.0;
+        }
+        (offset.x / self.max_distance).clamp(-1.0, 1.0)
+    }
+}
+
+// The ears are placed half a unit either side of the origin, and the sound
+// somewhere between them. Nothing is then further than one unit from an ear,
+// which keeps the spatial sink from attenuating it: the emitter's falloff
+// sets the volume instead.
+const EAR: Vec3 = Vec3::new(0.5, 0.0, 0.0);
+
+// Until `update_positional_sounds` first runs, Bevy places the sound using
+// scaled world coordinates. Shrinking them keeps that first moment from
+// being attenuated.
+const START_SCALE: f32 = 1.0e-4;
+
+fn listener_position(listener: &Query<&GlobalTransform, With<SoundListener>>) -> Vec2 {
+    listener
+        .iter()
+        .next()
+        .map_or(Vec2::ZERO, |transform| transform.translation().truncate())
+}
+
+fn channel_volume(audio: &GameAudio, channel: Option<&AudioChannel>) -> f32 {
+    channel.map_or(1.0, |channel| audio.volume(*channel))
+}
+
+/// Makes new positional sounds spatial, and sets their starting volume.
+/// This runs before Bevy starts them, so transforms aren't propagated yet.
+pub(crate) fn start_positional_sounds(
+    audio: Res<GameAudio>,
+    listener: Query<&GlobalTransform, With<SoundListener>>,
+    mut new_sounds: Query<
+        (
+            Entity,
+            &SoundEmitter,
+            Option<&AudioChannel>,
+            &mut PlaybackSettings,
+        ),
+        Added<SoundEmitter>,
+    >,
+    transforms: TransformHelper,
+) {
+    let listener = listener_position(&listener);
+    for (entity, emitter, channel, mut settings) in new_sounds.iter_mut() {
+        let position = transforms
+            .compute_global_transform(entity)
+            .map_or(listener, |transform| transform.translation().truncate());
+        settings.spatial = true;
+        settings.spatial_scale = Some(SpatialScale::new(START_SCALE));
+        settings.volume = Volume::Linear(
+            channel_volume(&audio, channel) * emitter.gain(position.distance(listener)),
+        );
+    }
+}
+
+/// Attenuates and pans playing positional sounds.
+pub(crate) fn update_positional_sounds(
+    audio: Res<GameAudio>,
+    listener: Query<&GlobalTransform, With<SoundListener>>,
+    mut sounds: Query<(
+        &SoundEmitter,
+        &GlobalTransform,
+        Option<&AudioChannel>,
+        &mut SpatialAudioSink,
+    )>,
+) {
+    let listener = listener_position(&listener);
+    for (emitter, transform, channel, mut sink) in sounds.iter_mut() {
+        let offset = transform.translation().truncate() - listener;
+        sink.set_ears_position(-EAR, EAR);
+        sink.set_emitter_position(EAR * emitter.pan(offset));
+        sink.set_volume(Volume::Linear(
+            channel_volume(&audio, channel) * emitter.gain(offset.length()),
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_falloff_curves() {
+        let emitter = SoundEmitter::new().with_range(100.0, 500.0);
+        assert_eq!(emitter.gain(50.0), 1.0);
+        assert_eq!(emitter.gain(200.0), 0.5);
+        assert_eq!(emitter.gain(600.0), 0.0);
+
+        let linear = emitter.with_falloff(Falloff::Linear);
+        assert_eq!(linear.gain(300.0), 0.5);
+        let quadratic = emitter.with_falloff(Falloff::Quadratic);
+        assert_eq!(quadratic.gain(300.0), 0.25);
+        let custom = emitter.with_falloff(Falloff::Custom(|t| 1.0 - t * t));
+        assert_eq!(custom.gain(300.0), 0.75);
+        let none = emitter.with_falloff(Falloff::None);
+        assert_eq!(none.gain(10_000.0), 1.0);
+    }
+
+    #[test]
+    fn test_pan() {
+        let emitter = SoundEmitter::new().with_range(0.0, 400.0);
+        assert_eq!(emitter.pan(Vec2::new(0.0, 300.0)), 0.0);
+        assert_eq!(emitter.pan(Vec2::new(-200.0, 0.0)), -0.5);
+        assert_eq!(emitter.pan(Vec2::new(800.0, 0.0)), 1.0);
+    }
+
+    #[test]
+    fn test_new_sounds_are_attenuated() {
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            AssetPlugin::default(),
+            TransformPlugin,
+            GameAudio::new().with_volume(AudioChannel::Sfx, 0.5),
+        ))
+        .init_asset::<AudioSource>();
+        app.world_mut()
+            .spawn((SoundListener, Transform::from_xyz(100.0, 0.0, 0.0)));
+        app.update();
+        let sound = app
+            .world_mut()
+            .spawn((
+                PlaybackSettings::DESPAWN,
+                AudioChannel::Sfx,
+                SoundEmitter::new().with_range(100.0, 1000.0),
+                Transform::from_xyz(300.0, 0.0, 0.0),
+            ))
+            .id();
+        app.update();
+
+        let settings = app.world().get::<PlaybackSettings>(sound).unwrap();
+        assert!(settings.spatial);
+        assert_eq!(settings.volume, Volume::Linear(0.25));
+    }
+}
@@ -13,6 +13,16 @@ impl Rect2D {
         Self { min, max }
     }
 
+    /// The corner with the smallest x and y coordinates.
+    pub fn min(&self) -> Vec2 {
+        self.min
+    }
+
+    /// The corner with the largest x and y coordinates.
+    pub fn max(&self) -> Vec2 {
+        self.max
+    }
+
     pub fn intersect(&self, other: &Rect2D) -> bool {
         self.min.x < other.max.x
             && self.max.x > other.min.x
@@ -5,6 +5,43 @@ use crate::{AssetResource, AssetStore, MenuResource};
 #[derive(Component)]
 pub(crate) struct MenuElement;
 
+/// Describes what a menu screen shows: an image, a title and/or a
+/// background color. Image tags must be added to the `AssetManager`,
+/// either loaded at startup or, for the game over screen, in an asset
+/// group that is held while it is shown; otherwise the game stops at
+/// startup.
+#[derive(Clone, Debug, Default)]
+pub struct MenuDescription {
+    pub(crate) image: Option<String>,
+    pub(crate) title: Option<String>,
+    pub(crate) background: Option<Color>,
+}
+
+impl MenuDescription {
+    /// Creates an empty menu description.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Shows the image registered under `tag`.
+    pub fn with_image<S: ToString>(mut self, tag: S) -> Self {
+        self.image = Some(tag.to_string());
+        self
+    }
+
+    /// Shows `title` as text in the middle of the screen.
+    pub fn with_title<S: ToString>(mut self, title: S) -> Self {
+        self.title = Some(title.to_string());
+        self
+    }
+
+    /// Clears the screen to `color` behind the menu.
+    pub fn with_background(mut self, color: Color) -> Self {
+        self.background = Some(color);
+        self
+    }
+}
+
 pub(crate) fn setup<T>(
     state: Res<State<T>>,
     mut commands: Commands,
@@ -15,26 +52,48 @@ pub(crate) fn setup<T>(
     T: States + FromWorld + FreelyMutableState,
 {
     let current_state = state.get();
-    let menu_graphic = {
+    let menu = {
         if menu_resource.menu_state == *current_state {
-            assets.get_handle("main_menu", &loaded_assets).unwrap()
+            &menu_resource.main_menu
         } else if menu_resource.game_end_state == *current_state {
-            assets.get_handle("game_over", &loaded_assets).unwrap()
+            &menu_resource.game_over
         } else {
             panic!("Unknown menu state")
         }
     };
 
-    commands.spawn(Camera2d::default()).insert(MenuElement);
-    commands
-        .spawn((
-            Sprite {
-                image: menu_graphic,
-                ..default()
-            },
-            Transform::from_xyz(0.0, 0.0, 1.0),
-        ))
-        .insert(MenuElement);
+    let mut camera = commands.spawn(Camera2d);
+    camera.insert(MenuElement);
+    if let Some(background) = menu.background {
+        camera.insert(Camera {
+            clear_color: ClearColorConfig::Custom(background),
+            ..default()
+        });
+    }
+
+    if let Some(image) = &menu.image {
+        match assets.try_get_sprite(image, &loaded_assets) {
+            Ok(sprite) => {
+                commands
+                    .spawn((sprite, Transform::from_xyz(0.0, 0.0, 1.0)))
+                    .insert(MenuElement);
+            }
+            Err(error) => warn!("Unable to show the menu image: {error}"),
+        }
+    }
+
+    if let Some(title) = &menu.title {
+        commands
+            .spawn((
+                Text2d::new(title.clone()),
+                TextFont {
+                    font_size: 32.0,
+                    ..default()
+                },
+                Transform::from_xyz(0.0, 0.0, 2.0),
+            ))
+            .insert(MenuElement);
+    }
 }
 
 pub(crate) fn run<T>(
@@ -1,6 +1,8 @@
+use std::time::Duration;
+
 use bevy::{prelude::*, state::state::FreelyMutableState};
 
-use crate::{bevy_assets, bevy_framework::bevy_physics::*};
+use crate::{bevy_assets, bevy_audio::play_state_music, bevy_framework::bevy_physics::*};
 
 mod bevy_physics;
 pub use bevy_physics::*;
@@ -9,11 +11,17 @@ mod bevy_collision;
 pub use bevy_collision::*;
 
 mod game_menus;
+pub use game_menus::MenuDescription;
 
 pub struct GameStatePlugin<T> {
     menu_state: T,
     game_start_state: T,
     game_end_state: T,
+    main_menu: MenuDescription,
+    game_over: MenuDescription,
+    min_loading_time: Duration,
+    asset_groups: Vec<AssetGroupState<T>>,
+    music: Vec<(T, String)>,
 }
 
 impl<T> GameStatePlugin<T>
@@ -26,8 +34,82 @@ where
             menu_state,
             game_start_state,
             game_end_state,
+            main_menu: MenuDescription::new().with_image("main_menu"),
+            game_over: MenuDescription::new().with_image("game_over"),
+            min_loading_time: Duration::ZERO,
+            asset_groups: vec![],
+            music: vec![],
         }
     }
+
+    /// Sets what the main menu shows. By default, it shows the image
+    /// registered as `main_menu`.
+    pub fn with_main_menu(mut self, menu: MenuDescription) -> Self {
+        self.main_menu = menu;
+        self
+    }
+
+    /// Sets what the game over screen shows. By default, it shows the image
+    /// registered as `game_over`.
+    pub fn with_game_over(mut self, menu: MenuDescription) -> Self {
+        self.game_over = menu;
+        self
+    }
+
+    /// Keeps the loading screen up for at least `duration`, even if every
+    /// asset loads sooner.
+    pub fn with_minimum_loading_time(mut self, duration: Duration) -> Self {
+        self.min_loading_time = duration;
+        self
+    }
+
+    /// Loads the asset `group` when entering `loading_state`, showing the
+    /// loading screen until it is ready, and then moves to `next_state`.
+    /// The group is released when leaving `next_state`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `loading_state` is the default state, which already loads
+    /// the assets needed at startup, or if another group already loads in
+    /// `loading_state`.
+    pub fn with_asset_group<S: ToString>(
+        mut self,
+        group: S,
+        loading_state: T,
+        next_state: T,
+    ) -> Self
+    where
+        T: Default,
+    {
+        let group = group.to_string();
+        if loading_state == T::default() {
+            panic!("Asset group '{group}' needs its own loading state, not {loading_state:?}");
+        }
+        if let Some(other) = self
+            .asset_groups
+            .iter()
+            .find(|binding| binding.loading_state == loading_state)
+        {
+            panic!(
+                "Asset groups '{}' and '{group}' can't share the loading state {loading_state:?}",
+                other.group
+            );
+        }
+        self.asset_groups.push(AssetGroupState {
+            group,
+            loading_state,
+            next_state,
+        });
+        self
+    }
+
+    /// Cross-fades to the background music `tag` when entering `state`.
+    /// States without music keep whatever was playing. Needs the
+    /// [`GameAudio`](crate::GameAudio) plugin.
+    pub fn with_music<S: ToString>(mut self, state: T, tag: S) -> Self {
+        self.music.push((state, tag.to_string()));
+        self
+    }
 }
 
 impl<T> Plugin for GameStatePlugin<T>
@@ -47,8 +129,13 @@ where
             menu_state: self.menu_state,
             game_start_state: self.game_start_state,
             game_end_state: self.game_end_state,
+            main_menu: self.main_menu.clone(),
+            game_over: self.game_over.clone(),
+            min_loading_time: self.min_loading_time,
+            asset_groups: self.asset_groups.clone(),
         };
         app.insert_resource(start);
+        app.add_event::<crate::AssetLoadFailed>();
 
         app.add_systems(OnEnter(self.menu_state), game_menus::setup::<T>);
         app.add_systems(
@@ -70,7 +157,95 @@ where
         app.add_systems(OnEnter(T::default()), crate::bevy_assets::setup);
         app.add_systems(Update, bevy_assets::run::<T>.run_if(in_state(T::default())));
         app.add_systems(OnExit(T::default()), crate::bevy_assets::exit);
+
+        for binding in self.asset_groups.iter() {
+            app.add_systems(
+                OnEnter(binding.loading_state),
+                bevy_assets::setup_group(binding.group.clone()),
+            );
+            app.add_systems(
+                Update,
+                bevy_assets::run::<T>.run_if(in_state(binding.loading_state)),
+            );
+            app.add_systems(OnExit(binding.loading_state), bevy_assets::exit);
+            app.add_systems(
+                OnExit(binding.next_state),
+                bevy_assets::release_group(binding.group.clone()),
+            );
+        }
+
+        for (state, tag) in self.music.iter() {
+            app.add_systems(OnEnter(*state), play_state_music(tag.clone()));
+        }
+    }
+
+    fn finish(&self, app: &mut App) {
+        // Every plugin has been added by now, so the AssetManager knows
+        // all of the assets and asset groups. Without one, no assets were
+        // registered at all.
+        let no_assets = crate::AssetManager::new();
+        let asset_manager = app
+            .world()
+            .get_resource::<crate::AssetManager>()
+            .unwrap_or(&no_assets);
+        let errors = self.menu_image_errors(asset_manager);
+        if !errors.is_empty() {
+            panic!(
+                "The game menus can't show their images:\n{}",
+                errors.join("\n")
+            );
+        }
+    }
+}
+
+impl<T> GameStatePlugin<T>
+where
+    T: States + Copy,
+{
+    fn menu_image_errors(&self, asset_manager: &crate::AssetManager) -> Vec<String> {
+        // The main menu is entered after the initial load and straight from
+        // the game over screen, so no asset group is ever loaded for it.
+        let no_groups: &[AssetGroupState<T>] = &[];
+        [
+            (self.menu_state, &self.main_menu, no_groups),
+            (self.game_end_state, &self.game_over, &self.asset_groups[..]),
+        ]
+        .into_iter()
+        .filter_map(|(state, menu, asset_groups)| {
+            menu_image_error(asset_manager, asset_groups, state, menu.image.as_ref()?)
+        })
+        .collect()
+    }
+}
+
+/// Checks that a menu image will be available while `state` is active:
+/// either it is loaded at startup, or it belongs to an asset group that is
+/// held in `state`.
+fn menu_image_error<T: PartialEq>(
+    asset_manager: &crate::AssetManager,
+    asset_groups: &[AssetGroupState<T>],
+    state: T,
+    tag: &str,
+) -> Option<String> {
+    if asset_manager.asset_list.iter().any(|(t, _, _)| t == tag) {
+        return None;
+    }
+    let groups: Vec<&String> = asset_manager
+        .groups
+        .iter()
+        .filter(|(_, assets)| assets.iter().any(|(t, _, _)| t == tag))
+        .map(|(group, _)| group)
+        .collect();
+    if groups.is_empty() {
+        let error = crate::AssetError::Unknown {
+            tag: tag.to_string(),
+        };
+        return Some(error.to_string());
     }
+    let held = asset_groups
+        .iter()
+        .any(|binding| binding.next_state == state && groups.contains(&&binding.group));
+    (!held).then(|| format!("asset '{tag}' belongs to a group that isn't loaded for its menu"))
 }
 
 pub fn cleanup<T>(query: Query<(Entity, &T)>, mut commands: Commands)
@@ -82,17 +257,22 @@ where
     });
 }
 
-#[derive(Resource)]
-pub(crate) struct MenuAssets {
-    pub(crate) main_menu: Handle<Image>,
-    pub(crate) game_over: Handle<Image>,
-}
-
 #[derive(Resource)]
 pub(crate) struct MenuResource<T> {
     pub(crate) menu_state: T,
     pub(crate) game_start_state: T,
     pub(crate) game_end_state: T,
+    pub(crate) main_menu: MenuDescription,
+    pub(crate) game_over: MenuDescription,
+    pub(crate) min_loading_time: Duration,
+    pub(crate) asset_groups: Vec<AssetGroupState<T>>,
+}
+
+#[derive(Clone)]
+pub(crate) struct AssetGroupState<T> {
+    pub(crate) group: String,
+    pub(crate) loading_state: T,
+    pub(crate) next_state: T,
 }
 
 #[macro_export]
@@ -108,3 +288,93 @@ macro_rules! add_phase {
         $($app.add_systems(bevy::prelude::OnExit::<$type>($phase), $exit_system);)*
   };
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AssetManager, bevy_assets::asset_manager::AssetType};
+
+    #[derive(States, Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+    enum TestState {
+        #[default]
+        Loading,
+        Menu,
+        LoadingMenu,
+        LoadingLevel,
+        Playing,
+        LoadingFinale,
+        GameOver,
+    }
+
+    fn image(manager: &mut AssetManager, tag: &str) {
+        manager.push_asset(tag.to_string(), format!("{tag}.png"), AssetType::Image);
+    }
+
+    #[test]
+    fn test_menu_images_must_be_available() {
+        let mut manager = AssetManager::new();
+        image(&mut manager, "main_menu");
+        let mut manager = manager.group("menu");
+        image(&mut manager, "menu_art");
+        let mut manager = manager.group("finale");
+        image(&mut manager, "finale_art");
+        let mut manager = manager.group("level");
+        image(&mut manager, "level_art");
+
+        let plugin = |main_menu: &str, game_over: &str| {
+            GameStatePlugin::new(TestState::Menu, TestState::Playing, TestState::GameOver)
+                .with_main_menu(MenuDescription::new().with_image(main_menu))
+                .with_game_over(MenuDescription::new().with_image(game_over))
+                .with_asset_group("menu", TestState::LoadingMenu, TestState::Menu)
+                .with_asset_group("finale", TestState::LoadingFinale, TestState::GameOver)
+                .with_asset_group("level", TestState::LoadingLevel, TestState::Playing)
+        };
+        let check = |main_menu, game_over| plugin(main_menu, game_over).menu_image_errors(&manager);
+
+        assert!(check("main_menu", "main_menu").is_empty());
+        assert!(check("main_menu", "finale_art").is_empty());
+        // Nothing loads a group on the way to the main menu.
+        assert_eq!(
+            check("menu_art", "main_menu"),
+            vec!["asset 'menu_art' belongs to a group that isn't loaded for its menu"]
+        );
+        assert_eq!(
+            check("missing", "level_art"),
+            vec![
+                "no asset was registered with the tag 'missing'",
+                "asset 'level_art' belongs to a group that isn't loaded for its menu",
+            ]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "needs its own loading state")]
+    fn test_asset_group_cant_load_in_default_state() {
+        GameStatePlugin::new(TestState::Menu, TestState::Playing, TestState::GameOver)
+            .with_asset_group("menu", TestState::Loading, TestState::Menu);
+    }
+
+    #[test]
+    #[should_panic(expected = "can't share the loading state")]
+    fn test_asset_groups_cant_share_a_loading_state() {
+        GameStatePlugin::new(TestState::Menu, TestState::Playing, TestState::GameOver)
+            .with_asset_group("level", TestState::LoadingLevel, TestState::Playing)
+            .with_asset_group("finale", TestState::LoadingLevel, TestState::GameOver);
+    }
+
+    #[test]
+    #[should_panic(expected = "can't show their images")]
+    fn test_unregistered_menu_image_fails_at_startup() {
+        let mut app = App::new();
+        app.insert_resource(AssetManager::new());
+        GameStatePlugin::new(TestState::Menu, TestState::Playing, TestState::GameOver)
+            .finish(&mut app);
+    }
+
+    #[test]
+    #[should_panic(expected = "no asset was registered with the tag 'main_menu'")]
+    fn test_missing_asset_manager_fails_at_startup() {
+        GameStatePlugin::new(TestState::Menu, TestState::Playing, TestState::GameOver)
+            .finish(&mut App::new());
+    }
+}
@@ -0,0 +1,633 @@
+use rand::Rng;
+use std::{fmt, str::FromStr};
+
+/// The largest number of dice (or sides) accepted by the dice parser.
+/// Keeps a typo like `"100000000d6"` from stalling the game.
+const MAX_DICE: u32 = 10_000;
+
+/// The largest modifier (positive or negative) a dice roll may add to its
+/// total.
+const MAX_MODIFIER: i32 = 1_000_000;
+
+/// Upper bound on the number of extra dice a single exploding die may
+/// add, so that a pathological run of maximum rolls always terminates.
+const MAX_EXPLOSIONS: u32 = 100;
+
+/// `DiceType` describes the classic `NdS+B` roll: roll `n_dice` dice with
+/// `die_type` sides each, and add `bonus` to the total.
+///
+/// It converts into a [`DiceRoll`], so it can be passed anywhere a
+/// `DiceRoll` is accepted. As with parsed dice, the number of dice and
+/// sides are capped at 10,000, and the bonus at ±1,000,000.
+///
+/// ## Example
+///
+/// ```
+/// use my_library::{DiceType, RandomNumberGenerator};
+/// let mut rng = RandomNumberGenerator::new();
+/// let three_d_six = rng.roll(DiceType::new(3, 6, 0));
+/// assert!((3..=18).contains(&three_d_six));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DiceType {
+    /// How many dice to roll.
+    pub n_dice: u32,
+    /// How many sides each die has.
+    pub die_type: u32,
+    /// A flat modifier added to the total.
+    pub bonus: i32,
+}
+
+impl DiceType {
+    /// Creates a new `DiceType`.
+    ///
+    /// # Arguments
+    ///
+    /// * `n_dice` - the number of dice to roll.
+    /// * `die_type` - the number of sides on each die.
+    /// * `bonus` - the modifier to add to the total (may be negative).
+    pub fn new(n_dice: u32, die_type: u32, bonus: i32) -> Self {
+        Self {
+            n_dice,
+            die_type,
+            bonus,
+        }
+    }
+}
+
+impl From<DiceType> for DiceRoll {
+    fn from(dice: DiceType) -> Self {
+        Self {
+            count: dice.n_dice.min(MAX_DICE),
+            sides: dice.die_type.min(MAX_DICE),
+            exploding: false,
+            keep: None,
+            modifier: dice.bonus.clamp(-MAX_MODIFIER, MAX_MODIFIER),
+        }
+    }
+}
+
+/// Selects which dice count towards the total of a [`DiceRoll`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiceKeep {
+    /// Keep the highest `n` dice (`4d6kh3`).
+    Highest(u32),
+    /// Keep the lowest `n` dice (`2d20kl1`).
+    Lowest(u32),
+    /// Discard the highest `n` dice (`4d6dh1`).
+    DropHighest(u32),
+    /// Discard the lowest `n` dice (`4d6dl1`).
+    DropLowest(u32),
+}
+
+/// `DiceRoll` is a parsed dice expression, ready to be rolled as many
+/// times as you like. Create one by parsing notation, or by converting a
+/// [`DiceType`].
+///
+/// The supported notation is `NdS`, optionally followed (in this order) by:
+///
+/// * `!` - exploding dice: every die showing its maximum is rolled again,
+///   and the new result added.
+/// * `khK`/`kK`, `klK`, `dhK`, `dlK` - keep the highest, keep the lowest,
+///   drop the highest or drop the lowest `K` dice.
+/// * Any number of `+M` / `-M` modifiers.
+///
+/// `N` may be omitted (`d20` is one twenty-sided die), and `%` may be used
+/// in place of `100` for the number of sides. Whitespace and case are
+/// ignored.
+///
+/// ## Example
+///
+/// ```
+/// use my_library::{DiceKeep, DiceRoll};
+/// let stat: DiceRoll = "4d6dl1".parse().unwrap();
+/// assert_eq!(stat.count(), 4);
+/// assert_eq!(stat.sides(), 6);
+/// assert_eq!(stat.keep(), Some(DiceKeep::DropLowest(1)));
+/// assert_eq!(stat.min(), 3);
+/// assert_eq!(stat.max(), 18);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DiceRoll {
+    count: u32,
+    sides: u32,
+    exploding: bool,
+    keep: Option<DiceKeep>,
+    modifier: i32,
+}
+
+impl DiceRoll {
+    /// Parses a dice expression such as `"3d6+2"` or `"4d6kh3"`.
+    ///
+    /// # Arguments
+    ///
+    /// * `notation` - the dice expression to parse.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use my_library::DiceRoll;
+    /// let fireball = DiceRoll::parse("8d6").unwrap();
+    /// assert_eq!(fireball.max(), 48);
+    /// assert!(DiceRoll::parse("8d").is_err());
+    /// ```
+    pub fn parse(notation: &str) -> Result<Self, DiceParseError> {
+        DiceParser::new(notation).parse()
+    }
+
+    /// How many dice to roll.
+    pub fn count(&self) -> u32 {
+        self.count
+    }
+
+    /// How many sides each die has.
+    pub fn sides(&self) -> u32 {
+        self.sides
+    }
+
+    /// Do dice showing their highest face roll again?
+    pub fn exploding(&self) -> bool {
+        self.exploding
+    }
+
+    /// Which dice count towards the total (all of them if `None`).
+    pub fn keep(&self) -> Option<DiceKeep> {
+        self.keep
+    }
+
+    /// The flat modifier added to the total.
+    pub fn modifier(&self) -> i32 {
+        self.modifier
+    }
+
+    /// The number of dice that count towards the total.
+    fn kept(&self) -> u32 {
+        match self.keep {
+            None => self.count,
+            Some(DiceKeep::Highest(n)) | Some(DiceKeep::Lowest(n)) => n.min(self.count),
+            Some(DiceKeep::DropHighest(n)) | Some(DiceKeep::DropLowest(n)) => {
+                self.count.saturating_sub(n)
+            }
+        }
+    }
+
+    /// The lowest total this roll can produce.
+    pub fn min(&self) -> i32 {
+        i32::try_from(self.kept())
+            .unwrap_or(i32::MAX)
+            .saturating_add(self.modifier)
+    }
+
+    /// The highest total this roll can produce. Exploding dice have no
+    /// real maximum, so this reports the highest total without explosions.
+    /// Totals too large for an `i32` are reported as `i32::MAX`.
+    pub fn max(&self) -> i32 {
+        i32::try_from(u64::from(self.kept()) * u64::from(self.sides))
+            .unwrap_or(i32::MAX)
+            .saturating_add(self.modifier)
+    }
+
+    /// Rolls the dice with the provided generator, returning the total.
+    /// Dice with no sides always roll zero.
+    pub(crate) fn roll_with<R: Rng + ?Sized>(&self, rng: &mut R) -> i32 {
+        if self.sides == 0 {
+            return self.modifier;
+        }
+        let mut rolls: Vec<i32> = (0..self.count)
+            .map(|_| {
+                let mut total: i32 = 0;
+                let mut explosions = 0;
+                loop {
+                    let die = rng.gen_range(1..=self.sides) as i32;
+                    total = total.saturating_add(die);
+                    if !self.exploding
+                        || die != self.sides as i32
+                        || explosions == MAX_EXPLOSIONS
+                    {
+                        break;
+                    }
+                    explosions += 1;
+                }
+                total
+            })
+            .collect();
+
+        let kept = self.kept() as usize;
+        let kept_rolls = match self.keep {
+            None => &rolls[..],
+            Some(DiceKeep::Highest(_)) | Some(DiceKeep::DropLowest(_)) => {
+                rolls.sort_unstable_by(|a, b| b.cmp(a));
+                &rolls[..kept]
+            }
+            Some(DiceKeep::Lowest(_)) | Some(DiceKeep::DropHighest(_)) => {
+                rolls.sort_unstable();
+                &rolls[..kept]
+            }
+        };
+
+        kept_rolls
+            .iter()
+            .fold(self.modifier, |total, roll| total.saturating_add(*roll))
+    }
+}
+
+/// Parses a dice expression and rolls it with the provided generator,
+/// returning the total.
+pub(crate) fn roll_str<R: Rng + ?Sized>(
+    rng: &mut R,
+    notation: &str,
+) -> Result<i32, DiceParseError> {
+    Ok(DiceRoll::parse(notation)?.roll_with(rng))
+}
+
+impl FromStr for DiceRoll {
+    type Err = DiceParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl fmt::Display for DiceRoll {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}d{}", self.count, self.sides)?;
+        if self.exploding {
+            write!(f, "!")?;
+        }
+        match self.keep {
+            None => {}
+            Some(DiceKeep::Highest(n)) => write!(f, "kh{n}")?,
+            Some(DiceKeep::Lowest(n)) => write!(f, "kl{n}")?,
+            Some(DiceKeep::DropHighest(n)) => write!(f, "dh{n}")?,
+            Some(DiceKeep::DropLowest(n)) => write!(f, "dl{n}")?,
+        }
+        match self.modifier {
+            0 => Ok(()),
+            m if m > 0 => write!(f, "+{m}"),
+            m => write!(f, "{m}"),
+        }
+    }
+}
+
+/// Describes why a dice expression could not be parsed. `position` fields
+/// are character offsets into the expression, ignoring whitespace.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiceParseError {
+    /// The expression was empty.
+    Empty,
+    /// A number was expected (for example after `d` or `+`).
+    ExpectedNumber {
+        /// Where the number was expected.
+        position: usize,
+    },
+    /// The `d` separating the dice count from the number of sides is missing.
+    MissingD {
+        /// Where the `d` was expected.
+        position: usize,
+    },
+    /// A number was too large to be useful.
+    NumberTooLarge {
+        /// Where the number starts.
+        position: usize,
+    },
+    /// The modifiers add up to more than 1,000,000 (or less than
+    /// -1,000,000).
+    ModifierTooLarge {
+        /// Where the modifier that overflowed starts.
+        position: usize,
+    },
+    /// Zero dice, or dice with zero sides, were requested.
+    ZeroDice,
+    /// `d1!` would explode forever.
+    ExplodingSingleSide,
+    /// More dice were kept or dropped than are rolled.
+    KeepTooMany {
+        /// The number of dice rolled.
+        count: u32,
+        /// The number of dice to keep or drop.
+        requested: u32,
+    },
+    /// Unexpected text followed an otherwise valid expression.
+    UnexpectedCharacter {
+        /// The offending character.
+        found: char,
+        /// Where the character was found.
+        position: usize,
+    },
+}
+
+impl fmt::Display for DiceParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => write!(f, "empty dice expression"),
+            Self::ExpectedNumber { position } => {
+                write!(f, "expected a number at position {position}")
+            }
+            Self::MissingD { position } => write!(f, "expected 'd' at position {position}"),
+            Self::NumberTooLarge { position } => write!(
+                f,
+                "number at position {position} is too large (maximum {MAX_DICE})"
+            ),
+            Self::ModifierTooLarge { position } => {
+                write!(
+                    f,
+                    "modifier at position {position} is too large (maximum ±{MAX_MODIFIER})"
+                )
+            }
+            Self::ZeroDice => write!(f, "dice count and sides must be at least 1"),
+            Self::ExplodingSingleSide => write!(f, "single-sided dice cannot explode"),
+            Self::KeepTooMany { count, requested } => write!(
+                f,
+                "cannot keep or drop {requested} dice when only {count} are rolled"
+            ),
+            Self::UnexpectedCharacter { found, position } => {
+                write!(f, "unexpected '{found}' at position {position}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for DiceParseError {}
+
+struct DiceParser {
+    chars: Vec<char>,
+    position: usize,
+}
+
+impl DiceParser {
+    fn new(notation: &str) -> Self {
+        Self {
+            chars: notation
+                .chars()
+                .filter(|c| !c.is_whitespace())
+                .map(|c| c.to_ascii_lowercase())
+                .collect(),
+            position: 0,
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).copied()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn number(&mut self) -> Result<Option<u32>, DiceParseError> {
+        let start = self.position;
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.position += 1;
+        }
+        if start == self.position {
+            return Ok(None);
+        }
+        let digits: String = self.chars[start..self.position].iter().collect();
+        match digits.parse::<u32>() {
+            Ok(n) if n <= MAX_DICE => Ok(Some(n)),
+            _ => Err(DiceParseError::NumberTooLarge { position: start }),
+        }
+    }
+
+    /// Reads a modifier's number, which isn't limited to `MAX_DICE`.
+    fn modifier_number(&mut self) -> Result<i32, DiceParseError> {
+        let start = self.position;
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.position += 1;
+        }
+        if start == self.position {
+            return Err(DiceParseError::ExpectedNumber { position: start });
+        }
+        let digits: String = self.chars[start..self.position].iter().collect();
+        digits
+            .parse::<i32>()
+            .map_err(|_| DiceParseError::ModifierTooLarge { position: start })
+    }
+
+    fn required_number(&mut self) -> Result<u32, DiceParseError> {
+        let position = self.position;
+        self.number()?
+            .ok_or(DiceParseError::ExpectedNumber { position })
+    }
+
+    fn parse(mut self) -> Result<DiceRoll, DiceParseError> {
+        if self.chars.is_empty() {
+            return Err(DiceParseError::Empty);
+        }
+
+        let count = self.number()?.unwrap_or(1);
+        if !self.eat('d') {
+            return Err(DiceParseError::MissingD {
+                position: self.position,
+            });
+        }
+        let sides = if self.eat('%') {
+            100
+        } else {
+            self.required_number()?
+        };
+        if count == 0 || sides == 0 {
+            return Err(DiceParseError::ZeroDice);
+        }
+
+        let exploding = self.eat('!');
+        if exploding && sides == 1 {
+            return Err(DiceParseError::ExplodingSingleSide);
+        }
+
+        let keep = if self.eat('k') {
+            if self.eat('l') {
+                Some(DiceKeep::Lowest(self.required_number()?))
+            } else {
+                self.eat('h');
+                Some(DiceKeep::Highest(self.required_number()?))
+            }
+        } else if self.eat('d') {
+            if self.eat('h') {
+                Some(DiceKeep::DropHighest(self.required_number()?))
+            } else {
+                self.eat('l');
+                Some(DiceKeep::DropLowest(self.required_number()?))
+            }
+        } else {
+            None
+        };
+        if let Some(
+            DiceKeep::Highest(requested)
+            | DiceKeep::Lowest(requested)
+            | DiceKeep::DropHighest(requested)
+            | DiceKeep::DropLowest(requested),
+        ) = keep
+            && requested > count
+        {
+            return Err(DiceParseError::KeepTooMany { count, requested });
+        }
+
+        let mut modifier = 0i32;
+        while let Some(sign) = self.peek() {
+            let sign = match sign {
+                '+' => 1,
+                '-' => -1,
+                found => {
+                    return Err(DiceParseError::UnexpectedCharacter {
+                        found,
+                        position: self.position,
+                    });
+                }
+            };
+            self.position += 1;
+            let position = self.position;
+            modifier = modifier
+                .checked_add(sign * self.modifier_number()?)
+                .filter(|total| total.abs() <= MAX_MODIFIER)
+                .ok_or(DiceParseError::ModifierTooLarge { position })?;
+        }
+
+        Ok(DiceRoll {
+            count,
+            sides,
+            exploding,
+            keep,
+            modifier,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_parse_basic() {
+        let roll = DiceRoll::parse("3d6+2").unwrap();
+        assert_eq!(
+            roll,
+            DiceRoll {
+                count: 3,
+                sides: 6,
+                exploding: false,
+                keep: None,
+                modifier: 2,
+            }
+        );
+        assert_eq!(DiceRoll::parse("d20").unwrap().count, 1);
+        assert_eq!(DiceRoll::parse("1D%").unwrap().sides, 100);
+        assert_eq!(DiceRoll::parse(" 2d8 - 1 + 3 ").unwrap().modifier, 2);
+    }
+
+    #[test]
+    fn test_parse_keep_and_explode() {
+        let roll = DiceRoll::parse("4d6!kh3").unwrap();
+        assert!(roll.exploding);
+        assert_eq!(roll.keep, Some(DiceKeep::Highest(3)));
+        assert_eq!(
+            DiceRoll::parse("2d20kl1").unwrap().keep,
+            Some(DiceKeep::Lowest(1))
+        );
+        assert_eq!(
+            DiceRoll::parse("4d6d1").unwrap().keep,
+            Some(DiceKeep::DropLowest(1))
+        );
+        assert_eq!(
+            DiceRoll::parse("4d6dh1").unwrap().keep,
+            Some(DiceKeep::DropHighest(1))
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(DiceRoll::parse(""), Err(DiceParseError::Empty));
+        assert_eq!(
+            DiceRoll::parse("3x6"),
+            Err(DiceParseError::MissingD { position: 1 })
+        );
+        assert_eq!(
+            DiceRoll::parse("3d"),
+            Err(DiceParseError::ExpectedNumber { position: 2 })
+        );
+        assert_eq!(DiceRoll::parse("0d6"), Err(DiceParseError::ZeroDice));
+        assert_eq!(
+            DiceRoll::parse("1d1!"),
+            Err(DiceParseError::ExplodingSingleSide)
+        );
+        assert_eq!(
+            DiceRoll::parse("2d6kh3"),
+            Err(DiceParseError::KeepTooMany {
+                count: 2,
+                requested: 3
+            })
+        );
+        assert_eq!(
+            DiceRoll::parse("2d6*2"),
+            Err(DiceParseError::UnexpectedCharacter {
+                found: '*',
+                position: 3
+            })
+        );
+        assert_eq!(
+            DiceRoll::parse("99999999d6"),
+            Err(DiceParseError::NumberTooLarge { position: 0 })
+        );
+    }
+
+    #[test]
+    fn test_display_round_trip() {
+        for notation in ["3d6", "4d6kh3", "2d20kl1", "1d10!+5", "4d6dl1-2"] {
+            let roll = DiceRoll::parse(notation).unwrap();
+            assert_eq!(roll.to_string(), notation);
+        }
+    }
+
+    #[test]
+    fn test_roll_bounds() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(1);
+        for notation in ["3d6+2", "4d6kh3", "4d6dl1", "2d20kl1", "1d4-3"] {
+            let roll = DiceRoll::parse(notation).unwrap();
+            for _ in 0..1000 {
+                let total = roll.roll_with(&mut rng);
+                assert!(total >= roll.min() && total <= roll.max());
+            }
+        }
+    }
+
+    #[test]
+    fn test_huge_rolls_do_not_overflow() {
+        let roll = DiceRoll::from(DiceType::new(u32::MAX, u32::MAX, 0));
+        assert_eq!((roll.count, roll.sides), (MAX_DICE, MAX_DICE));
+        assert_eq!(roll.max(), 100_000_000);
+
+        let mut rng = rand::rngs::StdRng::seed_from_u64(1);
+        let roll = DiceRoll::parse("10000d10000!+1000000").unwrap();
+        assert!(roll.roll_with(&mut rng) >= roll.min());
+    }
+
+    #[test]
+    fn test_modifier_limit() {
+        assert_eq!(DiceRoll::parse("1d6+1000000").unwrap().modifier, MAX_MODIFIER);
+        assert_eq!(DiceRoll::parse("1d6-20000").unwrap().modifier, -20_000);
+        assert_eq!(
+            DiceRoll::parse("1d6+999999+2"),
+            Err(DiceParseError::ModifierTooLarge { position: 11 })
+        );
+        assert_eq!(
+            DiceRoll::parse("1d6-99999999999"),
+            Err(DiceParseError::ModifierTooLarge { position: 4 })
+        );
+        let roll = DiceRoll::from(DiceType::new(1, 6, i32::MIN));
+        assert_eq!(roll.modifier(), -MAX_MODIFIER);
+    }
+
+    #[test]
+    fn test_exploding_exceeds_max() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(1);
+        let roll = DiceRoll::parse("1d2!").unwrap();
+        let exploded = (0..1000).any(|_| roll.roll_with(&mut rng) > roll.max());
+        assert!(exploded);
+    }
+}
@@ -4,11 +4,23 @@
 //!
 //! `my_library` includes:
 //!
-//! * Random number generation facilities.
+//! * Random number generation facilities, including normal, triangular,
+//!   exponential and Poisson distributions and random points and directions.
+//! * Named, independent random number streams derived from one seed.
+//! * Dice notation parsing and rolling (`"3d6+2"`, `"4d6kh3"`).
+//! * Weighted random tables ("loot tables").
+//! * Coherent noise (Perlin, Simplex and value noise, with fractal octaves
+//!   and domain warping) for procedural terrain.
+//! * Poisson-disc point sampling, for evenly spaced object placement.
+//! * Audio mixing with music, sound effect and interface channels, music
+//!   cross-fades and limits on overlapping sounds ([`GameAudio`]).
+//! * Positional sounds, attenuated and panned relative to a listener
+//!   ([`SoundEmitter`]).
 //!
 //! ## Feature Flags
 //!
-//! The following feature flags are supported: `xorshift`, `pcg`, `locking`.
+//! The following feature flags are supported: `xorshift`, `pcg`, `locking`,
+//! `serde`, `hot_reload`.
 //!
 //! ### Random Number Generation
 //!
@@ -16,10 +28,30 @@
 //! [`RandomNumberGenerator`],
 //! allowing it to be used as a resource (`Res<RandomNumberGenerator`)
 //! rather than requiring mutability (`ResMut<RandomNumberGenerator>`)
+//! * Systems that take the [`Random`] system parameter, and functions
+//!   generic over [`RandomSource`], compile with or without `locking`.
 //! * You can control which random number generation algorithm is used by
 //! specifying *one* of:
 //! * `xorshift` to use the XorShift algorithm.
 //! * `pcg` to use the PCG algorithm.
+//!
+//! `pcg` is enabled by default; if both are enabled, `xorshift` is used.
+//!
+//! ### Serialization
+//!
+//! * The `serde` feature derives `Serialize` and `Deserialize` for data
+//!   types such as [`RandomTable`], so they can be loaded from data files,
+//!   and for [`RngSnapshot`], so random number generator state can be
+//!   stored in save games and replays.
+//! * It also enables [`AssetManager::from_manifest`], which loads the list
+//!   of assets from a RON or JSON file, and animation files
+//!   ([`AssetManager::add_animations`]).
+//!
+//! ### Development
+//!
+//! * The `hot_reload` feature enables Bevy's file watching, so that
+//!   [`AssetManager::with_hot_reload`] can reload assets as they are
+//!   edited. Leave it off in release builds.
 
 #![warn(missing_docs)]
 
@@ -37,6 +69,29 @@ mod random_locking;
 #[cfg(feature = "locking")]
 pub use random_locking::*;
 
+mod dice;
+pub use dice::*;
+
+mod random_table;
+pub use random_table::*;
+
+mod random_distributions;
+
+mod random_streams;
+pub use random_streams::*;
+
+mod random_plugin;
+pub use random_plugin::*;
+
+mod random_source;
+pub use random_source::*;
+
+mod noise;
+pub use noise::*;
+
+mod poisson_disc;
+pub use poisson_disc::*;
+
 pub mod anyhow {
     //! Re-exports the `anyhow` crate for your convenience.
     pub use anyhow::*;
@@ -48,6 +103,9 @@ pub use bevy_framework::*;
 mod bevy_assets;
 pub use bevy_assets::*;
 
+mod bevy_audio;
+pub use bevy_audio::*;
+
 pub mod egui {
     pub use bevy_egui::*;
 }
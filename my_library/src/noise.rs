@@ -0,0 +1,314 @@
+use crate::RandomSource;
+use bevy::math::Vec2;
+
+/// The basic noise algorithm used by a [`Noise`] generator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NoiseType {
+    /// Classic gradient noise. Smooth, with a slightly "square" look.
+    Perlin,
+    /// Simplex noise: similar to Perlin, with fewer directional artifacts.
+    Simplex,
+    /// Interpolated random values. Blobby, and cheap to compute.
+    Value,
+}
+
+/// How octaves of noise are layered together by a [`Noise`] generator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FractalType {
+    /// A single octave.
+    None,
+    /// Fractal Brownian motion: each octave adds finer, fainter detail.
+    /// Good for rolling terrain and clouds.
+    Fbm,
+    /// Like `Fbm`, but folded to form sharp ridges. Good for mountain
+    /// ranges and cave networks.
+    Ridged,
+}
+
+/// `Noise` generates coherent 2D noise: random values that change smoothly
+/// from point to point, for procedural terrain, caves and backgrounds.
+///
+/// Noise is seeded from a random number generator, so the same seed
+/// always produces the same noise. Results are in the range `-1.0..=1.0`.
+///
+/// ## Example
+///
+/// ```
+/// use my_library::{FractalType, Noise, NoiseType, RandomNumberGenerator};
+/// let mut rng = RandomNumberGenerator::seeded(1);
+/// let terrain = Noise::new(NoiseType::Simplex, &mut rng)
+///     .with_frequency(0.05)
+///     .with_fractal(FractalType::Fbm, 4)
+///     .with_domain_warp(2.0);
+/// let height = terrain.get(10.0, 20.0);
+/// assert!((-1.0..=1.0).contains(&height));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Noise {
+    noise_type: NoiseType,
+    fractal: FractalType,
+    octaves: u32,
+    frequency: f32,
+    lacunarity: f32,
+    gain: f32,
+    warp_amplitude: f32,
+    permutation: Vec<u8>,
+}
+
+impl Noise {
+    /// Creates a new single-octave `Noise` generator, with a frequency of
+    /// `1.0`.
+    ///
+    /// # Arguments
+    ///
+    /// * `noise_type` - the noise algorithm to use.
+    /// * `rng` - the random number generator used to seed the noise.
+    pub fn new(noise_type: NoiseType, rng: &mut impl RandomSource) -> Self {
+        let mut permutation: Vec<u8> = (0..=255).collect();
+        rng.shuffle(&mut permutation);
+        permutation.extend_from_within(..);
+        Self {
+            noise_type,
+            fractal: FractalType::None,
+            octaves: 1,
+            frequency: 1.0,
+            lacunarity: 2.0,
+            gain: 0.5,
+            warp_amplitude: 0.0,
+            permutation,
+        }
+    }
+
+    /// Sets the frequency of the noise: how quickly it changes. Small
+    /// values give broad, gentle features.
+    ///
+    /// # Arguments
+    ///
+    /// * `frequency` - the frequency of the first octave.
+    pub fn with_frequency(mut self, frequency: f32) -> Self {
+        self.frequency = frequency;
+        self
+    }
+
+    /// Layers several octaves of noise together.
+    ///
+    /// # Arguments
+    ///
+    /// * `fractal` - how the octaves are combined.
+    /// * `octaves` - how many octaves to use.
+    pub fn with_fractal(mut self, fractal: FractalType, octaves: u32) -> Self {
+        self.fractal = fractal;
+        self.octaves = octaves.max(1);
+        self
+    }
+
+    /// Sets how much the frequency increases (default `2.0`) and the
+    /// amplitude decreases (default `0.5`) with each octave.
+    ///
+    /// # Arguments
+    ///
+    /// * `lacunarity` - the frequency multiplier between octaves.
+    /// * `gain` - the amplitude multiplier between octaves.
+    pub fn with_octave_scaling(mut self, lacunarity: f32, gain: f32) -> Self {
+        self.lacunarity = lacunarity;
+        self.gain = gain;
+        self
+    }
+
+    /// Distorts the input coordinates with another layer of noise before
+    /// sampling, for swirly, organic shapes.
+    ///
+    /// # Arguments
+    ///
+    /// * `amplitude` - how far (in input units) points may be moved.
+    pub fn with_domain_warp(mut self, amplitude: f32) -> Self {
+        self.warp_amplitude = amplitude;
+        self
+    }
+
+    /// Samples the noise at a point.
+    ///
+    /// # Arguments
+    ///
+    /// * `x` - the x coordinate.
+    /// * `y` - the y coordinate.
+    pub fn get(&self, x: f32, y: f32) -> f32 {
+        let mut point = Vec2::new(x, y);
+        if self.warp_amplitude != 0.0 {
+            let warp = Vec2::new(
+                self.sample(point * self.frequency + Vec2::new(5.2, 1.3)),
+                self.sample(point * self.frequency + Vec2::new(1.7, 9.2)),
+            );
+            point += warp * self.warp_amplitude;
+        }
+        self.fractal(point * self.frequency).clamp(-1.0, 1.0)
+    }
+
+    /// Samples the noise at a point.
+    ///
+    /// # Arguments
+    ///
+    /// * `point` - the point to sample.
+    pub fn get_vec2(&self, point: Vec2) -> f32 {
+        self.get(point.x, point.y)
+    }
+
+    fn fractal(&self, point: Vec2) -> f32 {
+        if self.fractal == FractalType::None {
+            return self.sample(point);
+        }
+        let mut total = 0.0;
+        let mut amplitude = 1.0;
+        let mut max_amplitude = 0.0;
+        let mut point = point;
+        for octave in 0..self.octaves {
+            // Offset each octave, so that they don't all line up at the origin.
+            let sample = self.sample(point + Vec2::splat(octave as f32 * 19.19));
+            total += amplitude
+                * match self.fractal {
+                    FractalType::Ridged => {
+                        let ridge = 1.0 - sample.abs();
+                        ridge * ridge * 2.0 - 1.0
+                    }
+                    _ => sample,
+                };
+            max_amplitude += amplitude;
+            amplitude *= self.gain;
+            point *= self.lacunarity;
+        }
+        total / max_amplitude
+    }
+
+    fn sample(&self, point: Vec2) -> f32 {
+        match self.noise_type {
+            NoiseType::Perlin => self.perlin(point),
+            NoiseType::Simplex => self.simplex(point),
+            NoiseType::Value => self.value(point),
+        }
+    }
+
+    fn hash(&self, x: i32, y: i32) -> u8 {
+        let x = (x & 255) as usize;
+        let y = (y & 255) as usize;
+        self.permutation[self.permutation[x] as usize + y]
+    }
+
+    fn value(&self, point: Vec2) -> f32 {
+        let cell = point.floor();
+        let (x, y) = (cell.x as i32, cell.y as i32);
+        let t = point - cell;
+        let corner = |dx, dy| self.hash(x + dx, y + dy) as f32 / 127.5 - 1.0;
+        let (u, v) = (fade(t.x), fade(t.y));
+        lerp(
+            lerp(corner(0, 0), corner(1, 0), u),
+            lerp(corner(0, 1), corner(1, 1), u),
+            v,
+        )
+    }
+
+    fn perlin(&self, point: Vec2) -> f32 {
+        let cell = point.floor();
+        let (x, y) = (cell.x as i32, cell.y as i32);
+        let t = point - cell;
+        let corner = |dx: i32, dy: i32| {
+            gradient(
+                self.hash(x + dx, y + dy),
+                t - Vec2::new(dx as f32, dy as f32),
+            )
+        };
+        let (u, v) = (fade(t.x), fade(t.y));
+        lerp(
+            lerp(corner(0, 0), corner(1, 0), u),
+            lerp(corner(0, 1), corner(1, 1), u),
+            v,
+        )
+    }
+
+    fn simplex(&self, point: Vec2) -> f32 {
+        const F2: f32 = 0.366_025_42; // (sqrt(3) - 1) / 2
+        const G2: f32 = 0.211_324_87; // (3 - sqrt(3)) / 6
+
+        // Find the simplex (triangle) containing the point.
+        let skew = (point.x + point.y) * F2;
+        let cell = (point + Vec2::splat(skew)).floor();
+        let unskew = (cell.x + cell.y) * G2;
+        let p0 = point - (cell - Vec2::splat(unskew));
+        let offset = if p0.x > p0.y { Vec2::X } else { Vec2::Y };
+        let p1 = p0 - offset + Vec2::splat(G2);
+        let p2 = p0 - Vec2::ONE + Vec2::splat(2.0 * G2);
+
+        let (x, y) = (cell.x as i32, cell.y as i32);
+        let corner = |p: Vec2, dx: i32, dy: i32| {
+            let falloff = 0.5 - p.length_squared();
+            if falloff < 0.0 {
+                0.0
+            } else {
+                falloff.powi(4) * gradient(self.hash(x + dx, y + dy), p)
+            }
+        };
+        70.0 * (corner(p0, 0, 0) + corner(p1, offset.x as i32, offset.y as i32) + corner(p2, 1, 1))
+    }
+}
+
+fn fade(t: f32) -> f32 {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
+
+fn gradient(hash: u8, offset: Vec2) -> f32 {
+    match hash & 7 {
+        0 => offset.x + offset.y,
+        1 => -offset.x + offset.y,
+        2 => offset.x - offset.y,
+        3 => -offset.x - offset.y,
+        4 => offset.x,
+        5 => -offset.x,
+        6 => offset.y,
+        _ => -offset.y,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RandomNumberGenerator;
+
+    const TYPES: [NoiseType; 3] = [NoiseType::Perlin, NoiseType::Simplex, NoiseType::Value];
+
+    #[test]
+    fn test_seeded_noise_is_reproducible() {
+        for noise_type in TYPES {
+            let a = Noise::new(noise_type, &mut RandomNumberGenerator::seeded(1));
+            let b = Noise::new(noise_type, &mut RandomNumberGenerator::seeded(1));
+            let c = Noise::new(noise_type, &mut RandomNumberGenerator::seeded(2));
+            let points: Vec<Vec2> = (0..100)
+                .map(|i| Vec2::new(i as f32 * 0.37, i as f32 * 0.71))
+                .collect();
+            assert!(points.iter().all(|p| a.get_vec2(*p) == b.get_vec2(*p)));
+            assert!(points.iter().any(|p| a.get_vec2(*p) != c.get_vec2(*p)));
+        }
+    }
+
+    #[test]
+    fn test_noise_is_bounded_and_smooth() {
+        let mut rng = RandomNumberGenerator::seeded(1);
+        for noise_type in TYPES {
+            for fractal in [FractalType::None, FractalType::Fbm, FractalType::Ridged] {
+                let noise = Noise::new(noise_type, &mut rng)
+                    .with_frequency(0.1)
+                    .with_fractal(fractal, 4)
+                    .with_domain_warp(1.0);
+                let mut previous = noise.get(0.0, 3.0);
+                for step in 1..2000 {
+                    let value = noise.get(step as f32 * 0.01, 3.0);
+                    assert!((-1.0..=1.0).contains(&value));
+                    assert!((value - previous).abs() < 0.1);
+                    previous = value;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,360 @@
+use crate::{RandomSource, Rect2D};
+use bevy::math::Vec2;
+use std::f32::consts::TAU;
+
+/// The most cells the spacing grid may have, which keeps a tiny
+/// `min_distance` from allocating without limit.
+const MAX_GRID_CELLS: usize = 1 << 24;
+
+/// `PoissonDiscSampler` scatters points over a region so that no two are
+/// closer than a minimum distance, while still looking random ("blue
+/// noise"). Unlike picking points uniformly, this never produces clumps,
+/// which makes it ideal for placing pickups, trees or enemies.
+///
+/// Points can be restricted to the parts of the region that pass a
+/// caller-supplied validity test, for example "is this tile empty?".
+///
+/// ## Example
+///
+/// ```
+/// use bevy::math::Vec2;
+/// use my_library::{PoissonDiscSampler, RandomNumberGenerator, Rect2D};
+/// let mut rng = RandomNumberGenerator::seeded(1);
+/// let region = Rect2D::new(Vec2::ZERO, Vec2::new(100.0, 100.0));
+/// let trees = PoissonDiscSampler::new(region, 10.0)
+///     .with_max_points(20)
+///     .sample(&mut rng);
+/// assert_eq!(trees.len(), 20);
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct PoissonDiscSampler {
+    region: Rect2D,
+    min_distance: f32,
+    max_points: Option<usize>,
+    attempts: u32,
+}
+
+impl PoissonDiscSampler {
+    /// Creates a new `PoissonDiscSampler`.
+    ///
+    /// # Arguments
+    ///
+    /// * `region` - the area in which to place points.
+    /// * `min_distance` - the smallest allowed distance between two points.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `min_distance` isn't a positive number, or is so small
+    /// compared to `region` that it would need millions of points.
+    pub fn new(region: Rect2D, min_distance: f32) -> Self {
+        assert!(
+            min_distance > 0.0 && min_distance.is_finite(),
+            "PoissonDiscSampler needs a positive min_distance, not {min_distance}"
+        );
+        let (_, columns, rows) = SpacingGrid::dimensions(region, min_distance);
+        assert!(
+            columns.saturating_mul(rows) <= MAX_GRID_CELLS,
+            "PoissonDiscSampler's min_distance of {min_distance} is too small for its region"
+        );
+        Self {
+            region,
+            min_distance,
+            max_points: None,
+            attempts: 30,
+        }
+    }
+
+    /// Limits how many points are returned. If more points fit, a random
+    /// selection of them is returned, so they still cover the whole region.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_points` - the largest number of points to return.
+    pub fn with_max_points(mut self, max_points: usize) -> Self {
+        self.max_points = Some(max_points);
+        self
+    }
+
+    /// Sets how many candidates are tried around each point before giving
+    /// up on it (default `30`). Higher values pack points more tightly, but
+    /// take longer.
+    ///
+    /// # Arguments
+    ///
+    /// * `attempts` - the number of candidates to try per point.
+    pub fn with_attempts(mut self, attempts: u32) -> Self {
+        self.attempts = attempts.max(1);
+        self
+    }
+
+    /// Scatters points over the whole region.
+    ///
+    /// # Arguments
+    ///
+    /// * `rng` - the random number generator to use.
+    pub fn sample(&self, rng: &mut impl RandomSource) -> Vec<Vec2> {
+        self.sample_where(rng, |_| true)
+    }
+
+    /// Scatters points over the parts of the region for which `is_valid`
+    /// returns `true`. Points grow outwards from randomly placed seeds; once
+    /// they can't grow any further, more seeds are tried, so separate valid
+    /// areas are filled if a seed lands in them. Small areas may be missed:
+    /// use `sample_from` to place points on them.
+    ///
+    /// # Arguments
+    ///
+    /// * `rng` - the random number generator to use.
+    /// * `is_valid` - returns `true` if a point may be placed at a position.
+    pub fn sample_where(
+        &self,
+        rng: &mut impl RandomSource,
+        is_valid: impl Fn(Vec2) -> bool,
+    ) -> Vec<Vec2> {
+        let Some(mut grid) = SpacingGrid::new(self.region, self.min_distance) else {
+            return Vec::new();
+        };
+        let (min, size) = (self.region.min(), self.region.max() - self.region.min());
+
+        let mut points: Vec<Vec2> = Vec::new();
+        let mut active: Vec<usize> = Vec::new();
+        let mut add_if_space = |point: Vec2, points: &mut Vec<Vec2>, active: &mut Vec<usize>| {
+            if !grid.has_room(point, points) || !is_valid(point) {
+                return false;
+            }
+            grid.insert(point, points);
+            active.push(points.len() - 1);
+            true
+        };
+
+        loop {
+            // Seed a new area: the first point, or one in a valid area that
+            // the points so far couldn't reach.
+            let seeded = (0..self.attempts * 10).any(|_| {
+                let point = min + Vec2::new(rng.next::<f32>(), rng.next::<f32>()) * size;
+                add_if_space(point, &mut points, &mut active)
+            });
+            if !seeded {
+                break;
+            }
+
+            while !active.is_empty() {
+                let active_index = rng.range(0..active.len());
+                let center = points[active[active_index]];
+                let found = (0..self.attempts).any(|_| {
+                    let angle = rng.range(0.0..TAU);
+                    let distance = rng.range(self.min_distance..self.min_distance * 2.0);
+                    let point = center + Vec2::from_angle(angle) * distance;
+                    add_if_space(point, &mut points, &mut active)
+                });
+                if !found {
+                    active.swap_remove(active_index);
+                }
+            }
+        }
+
+        self.limit(rng, points)
+    }
+
+    /// Picks well-spaced points from a list of candidates, such as the
+    /// centres of the tiles an item could stand on. This suits valid areas
+    /// too thin for `sample_where` to grow into, like single-tile floors.
+    /// Candidates outside the region are ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `rng` - the random number generator to use.
+    /// * `candidates` - the positions that points may be placed at.
+    pub fn sample_from(&self, rng: &mut impl RandomSource, candidates: &[Vec2]) -> Vec<Vec2> {
+        let Some(mut grid) = SpacingGrid::new(self.region, self.min_distance) else {
+            return Vec::new();
+        };
+        let mut candidates = candidates.to_vec();
+        rng.shuffle(&mut candidates);
+        let mut points = Vec::new();
+        for candidate in candidates {
+            if grid.has_room(candidate, &points) {
+                grid.insert(candidate, &mut points);
+            }
+        }
+        self.limit(rng, points)
+    }
+
+    fn limit(&self, rng: &mut impl RandomSource, points: Vec<Vec2>) -> Vec<Vec2> {
+        match self.max_points {
+            Some(max_points) if points.len() > max_points => rng
+                .choose_multiple(&points, max_points)
+                .into_iter()
+                .copied()
+                .collect(),
+            _ => points,
+        }
+    }
+}
+
+/// A grid over the sampled region whose cells are small enough to hold at
+/// most one point, so only the neighbouring cells need checking for points
+/// that are too close.
+struct SpacingGrid {
+    min: Vec2,
+    max: Vec2,
+    min_distance: f32,
+    cell_size: f32,
+    columns: usize,
+    rows: usize,
+    cells: Vec<Option<usize>>,
+}
+
+impl SpacingGrid {
+    fn new(region: Rect2D, min_distance: f32) -> Option<Self> {
+        let (min, max) = (region.min(), region.max());
+        let size = max - min;
+        if size.x <= 0.0 || size.y <= 0.0 {
+            return None;
+        }
+        let (cell_size, columns, rows) = Self::dimensions(region, min_distance);
+        Some(Self {
+            min,
+            max,
+            min_distance,
+            cell_size,
+            columns,
+            rows,
+            cells: vec![None; columns * rows],
+        })
+    }
+
+    /// The cell size, and the number of columns and rows, needed to cover
+    /// `region`.
+    fn dimensions(region: Rect2D, min_distance: f32) -> (f32, usize, usize) {
+        let size = region.max() - region.min();
+        let cell_size = min_distance / std::f32::consts::SQRT_2;
+        let columns = (size.x / cell_size).ceil() as usize;
+        let rows = (size.y / cell_size).ceil() as usize;
+        (cell_size, columns, rows)
+    }
+
+    fn cell_of(&self, point: Vec2) -> (usize, usize) {
+        let offset = (point - self.min) / self.cell_size;
+        (
+            (offset.x as usize).min(self.columns - 1),
+            (offset.y as usize).min(self.rows - 1),
+        )
+    }
+
+    /// Returns `true` if `point` is inside the region, and far enough from
+    /// every point placed so far.
+    fn has_room(&self, point: Vec2, points: &[Vec2]) -> bool {
+        if point.x < self.min.x
+            || point.y < self.min.y
+            || point.x >= self.max.x
+            || point.y >= self.max.y
+        {
+            return false;
+        }
+        let (column, row) = self.cell_of(point);
+        for y in row.saturating_sub(2)..(row + 3).min(self.rows) {
+            for x in column.saturating_sub(2)..(column + 3).min(self.columns) {
+                if let Some(other) = self.cells[y * self.columns + x]
+                    && points[other].distance_squared(point) < self.min_distance * self.min_distance
+                {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+
+    fn insert(&mut self, point: Vec2, points: &mut Vec<Vec2>) {
+        let (column, row) = self.cell_of(point);
+        self.cells[row * self.columns + column] = Some(points.len());
+        points.push(point);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RandomNumberGenerator;
+
+    fn assert_spaced(points: &[Vec2], min_distance: f32) {
+        for (i, a) in points.iter().enumerate() {
+            for b in points.iter().skip(i + 1) {
+                assert!(a.distance(*b) >= min_distance);
+            }
+        }
+    }
+
+    #[test]
+    fn test_points_are_spaced_and_fill_region() {
+        let mut rng = RandomNumberGenerator::seeded(1);
+        let region = Rect2D::new(Vec2::new(-50.0, -50.0), Vec2::new(50.0, 50.0));
+        let points = PoissonDiscSampler::new(region, 5.0).sample(&mut rng);
+        assert_spaced(&points, 5.0);
+        assert!(points.len() > 200);
+        assert!(
+            points
+                .iter()
+                .all(|p| (-50.0..50.0).contains(&p.x) && (-50.0..50.0).contains(&p.y))
+        );
+    }
+
+    #[test]
+    fn test_mask_and_max_points() {
+        let mut rng = RandomNumberGenerator::seeded(1);
+        let region = Rect2D::new(Vec2::ZERO, Vec2::new(100.0, 100.0));
+        // Two separate strips, which must both be filled.
+        let in_strip = |p: Vec2| p.x < 10.0 || p.x > 90.0;
+        let points = PoissonDiscSampler::new(region, 4.0).sample_where(&mut rng, in_strip);
+        assert_spaced(&points, 4.0);
+        assert!(points.iter().all(|p| in_strip(*p)));
+        assert!(points.iter().any(|p| p.x < 10.0) && points.iter().any(|p| p.x > 90.0));
+
+        let limited = PoissonDiscSampler::new(region, 4.0)
+            .with_max_points(10)
+            .sample_where(&mut rng, in_strip);
+        assert_eq!(limited.len(), 10);
+        assert_spaced(&limited, 4.0);
+    }
+
+    #[test]
+    fn test_sample_from_candidates() {
+        let mut rng = RandomNumberGenerator::seeded(1);
+        let region = Rect2D::new(Vec2::ZERO, Vec2::new(100.0, 100.0));
+        // A one-unit-thick floor, plus a candidate outside the region.
+        let mut floor: Vec<Vec2> = (0..100).map(|x| Vec2::new(x as f32, 20.0)).collect();
+        floor.push(Vec2::new(150.0, 20.0));
+        let points = PoissonDiscSampler::new(region, 8.0).sample_from(&mut rng, &floor);
+        assert_spaced(&points, 8.0);
+        assert!(points.len() >= 100 / 16);
+        assert!(points.iter().all(|p| p.y == 20.0 && p.x < 100.0));
+
+        let limited = PoissonDiscSampler::new(region, 8.0)
+            .with_max_points(5)
+            .sample_from(&mut rng, &floor);
+        assert_eq!(limited.len(), 5);
+    }
+
+    #[test]
+    #[should_panic(expected = "positive min_distance")]
+    fn test_zero_distance_is_rejected() {
+        PoissonDiscSampler::new(Rect2D::new(Vec2::ZERO, Vec2::ONE), 0.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "too small for its region")]
+    fn test_tiny_distance_is_rejected() {
+        let region = Rect2D::new(Vec2::ZERO, Vec2::new(1000.0, 1000.0));
+        PoissonDiscSampler::new(region, 0.001);
+    }
+
+    #[test]
+    fn test_seeded_reproducibility() {
+        let region = Rect2D::new(Vec2::ZERO, Vec2::new(100.0, 100.0));
+        let sampler = PoissonDiscSampler::new(region, 10.0);
+        assert_eq!(
+            sampler.sample(&mut RandomNumberGenerator::seeded(3)),
+            sampler.sample(&mut RandomNumberGenerator::seeded(3))
+        );
+    }
+}
@@ -1,11 +1,20 @@
-use rand::{Rng, SeedableRng, distributions::uniform::SampleRange};
+use bevy::math::Vec2;
+use rand::{Rng, SeedableRng, distributions::uniform::SampleRange, seq::SliceRandom};
+
+use crate::{
+    DiceParseError, DiceRoll, RandomTable, Rect2D, dice, random_distributions,
+    random_streams::derive_seed,
+};
 
 pub use rand;
 
+// `ChaCha12Rng` is the algorithm behind `rand`'s `StdRng`; it is named
+// directly because, unlike `StdRng`, its state can be serialized.
 #[cfg(all(not(feature = "pcg"), not(feature = "xorshift")))]
-type RngCore = rand::prelude::StdRng;
+type RngCore = rand_chacha::ChaCha12Rng;
 
-#[cfg(feature = "pcg")]
+// `pcg` is a default feature, so an explicitly enabled `xorshift` wins.
+#[cfg(all(feature = "pcg", not(feature = "xorshift")))]
 type RngCore = rand_pcg::Pcg64Mcg;
 
 #[cfg(feature = "xorshift")]
@@ -34,7 +43,7 @@ type RngCore = rand_xorshift::XorShiftRng;
 /// ```
 #[derive(bevy::prelude::Resource)]
 pub struct RandomNumberGenerator {
-    pub rng: RngCore,
+    pub(crate) rng: RngCore,
 }
 
 impl RandomNumberGenerator {
@@ -68,6 +77,69 @@ impl RandomNumberGenerator {
         }
     }
 
+    /// Creates a new `RandomNumberGenerator` from a previously captured
+    /// [`RngSnapshot`].
+    ///
+    /// # Arguments
+    ///
+    /// * `snapshot` - the state to start from.
+    pub fn from_snapshot(snapshot: &RngSnapshot) -> Self {
+        Self {
+            rng: snapshot.state.clone(),
+        }
+    }
+
+    /// Captures the current state of the generator.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use my_library::RandomNumberGenerator;
+    /// let mut rng = RandomNumberGenerator::seeded(1);
+    /// let snapshot = rng.snapshot();
+    /// let first: u32 = rng.next();
+    /// rng.restore(&snapshot);
+    /// assert_eq!(first, rng.next::<u32>());
+    /// ```
+    pub fn snapshot(&self) -> RngSnapshot {
+        RngSnapshot {
+            state: self.rng.clone(),
+        }
+    }
+
+    /// Restores the generator to a previously captured state. Subsequent
+    /// requests produce the same results they did after the snapshot was
+    /// taken.
+    ///
+    /// # Arguments
+    ///
+    /// * `snapshot` - the state to restore.
+    pub fn restore(&mut self, snapshot: &RngSnapshot) {
+        self.rng = snapshot.state.clone();
+    }
+
+    /// Derives a named child generator from the current state. The child
+    /// is independent of its parent, and forking does not advance the
+    /// parent: the same state and name always produce the same child.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - identifies the child stream, e.g. `"worldgen"`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use my_library::RandomNumberGenerator;
+    /// let rng = RandomNumberGenerator::seeded(1);
+    /// let worldgen = rng.fork("worldgen");
+    /// assert_eq!(worldgen.snapshot(), rng.fork("worldgen").snapshot());
+    /// assert_ne!(worldgen.snapshot(), rng.fork("combat").snapshot());
+    /// ```
+    pub fn fork(&self, name: &str) -> Self {
+        let base: u64 = self.rng.clone().r#gen();
+        Self::seeded(derive_seed(base, name, 0))
+    }
+
     /// Generates a random number within the specified range.
     ///
     /// # Arguments
@@ -97,25 +169,332 @@ impl RandomNumberGenerator {
     {
         self.rng.r#gen()
     }
-}
 
-impl Default for RandomNumberGenerator {
-    fn default() -> Self {
-        Self::new()
+    /// Draws from a normal (Gaussian) distribution: results cluster
+    /// around the mean, and about 68% of them fall within one standard
+    /// deviation of it.
+    ///
+    /// # Arguments
+    ///
+    /// * `mean` - the center of the distribution.
+    /// * `std_dev` - how widely results are spread around the mean.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `std_dev` is negative or not finite.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use my_library::RandomNumberGenerator;
+    /// let mut rng = RandomNumberGenerator::new();
+    /// let height = rng.normal(170.0, 10.0);
+    /// ```
+    pub fn normal(&mut self, mean: f32, std_dev: f32) -> f32 {
+        random_distributions::normal(&mut self.rng, mean, std_dev)
+    }
+
+    /// Draws from a triangular distribution: results fall between `min`
+    /// and `max`, and are most likely to be close to `mode`.
+    ///
+    /// # Arguments
+    ///
+    /// * `min` - the lowest possible result.
+    /// * `max` - the highest possible result.
+    /// * `mode` - the most likely result.
+    ///
+    /// # Panics
+    ///
+    /// Panics unless `min <= mode <= max`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use my_library::RandomNumberGenerator;
+    /// let mut rng = RandomNumberGenerator::new();
+    /// let lifetime = rng.triangular(1.0, 3.0, 1.5);
+    /// assert!((1.0..=3.0).contains(&lifetime));
+    /// ```
+    pub fn triangular(&mut self, min: f32, max: f32, mode: f32) -> f32 {
+        random_distributions::triangular(&mut self.rng, min, max, mode)
+    }
+
+    /// Draws from an exponential distribution: the time between events
+    /// that happen, on average, `lambda` times per unit of time.
+    ///
+    /// # Arguments
+    ///
+    /// * `lambda` - the average rate of events. The mean result is `1.0 / lambda`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `lambda` is negative.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use my_library::RandomNumberGenerator;
+    /// let mut rng = RandomNumberGenerator::new();
+    /// let seconds_until_next_spawn = rng.exponential(0.5);
+    /// assert!(seconds_until_next_spawn >= 0.0);
+    /// ```
+    pub fn exponential(&mut self, lambda: f32) -> f32 {
+        random_distributions::exponential(&mut self.rng, lambda)
+    }
+
+    /// Draws from a Poisson distribution: how many events occur in a unit
+    /// of time, if they happen on average `lambda` times per unit of time.
+    ///
+    /// # Arguments
+    ///
+    /// * `lambda` - the average number of events.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `lambda` is not positive and finite.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use my_library::RandomNumberGenerator;
+    /// let mut rng = RandomNumberGenerator::new();
+    /// let particles_this_frame = rng.poisson(3.0);
+    /// ```
+    pub fn poisson(&mut self, lambda: f32) -> u32 {
+        random_distributions::poisson(&mut self.rng, lambda)
+    }
+
+    /// Generates a vector of length one, pointing in a random direction.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use my_library::RandomNumberGenerator;
+    /// let mut rng = RandomNumberGenerator::new();
+    /// let direction = rng.random_unit_vec2();
+    /// assert!((direction.length() - 1.0).abs() < 0.001);
+    /// ```
+    pub fn random_unit_vec2(&mut self) -> Vec2 {
+        random_distributions::unit_vec2(&mut self.rng)
+    }
+
+    /// Generates a point inside a circle. Points are evenly distributed
+    /// over the circle's area.
+    ///
+    /// # Arguments
+    ///
+    /// * `center` - the center of the circle.
+    /// * `radius` - the radius of the circle.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bevy::math::Vec2;
+    /// use my_library::RandomNumberGenerator;
+    /// let mut rng = RandomNumberGenerator::new();
+    /// let point = rng.random_point_in_circle(Vec2::ZERO, 5.0);
+    /// assert!(point.length() <= 5.001);
+    /// ```
+    pub fn random_point_in_circle(&mut self, center: Vec2, radius: f32) -> Vec2 {
+        random_distributions::point_in_circle(&mut self.rng, center, radius)
+    }
+
+    /// Generates a point inside a rectangle.
+    ///
+    /// # Arguments
+    ///
+    /// * `rect` - the rectangle to pick a point within.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bevy::math::Vec2;
+    /// use my_library::{RandomNumberGenerator, Rect2D};
+    /// let mut rng = RandomNumberGenerator::new();
+    /// let screen = Rect2D::new(Vec2::new(-640.0, -360.0), Vec2::new(640.0, 360.0));
+    /// let point = rng.random_point_in_rect(screen);
+    /// assert!((-640.0..=640.0).contains(&point.x));
+    /// ```
+    pub fn random_point_in_rect(&mut self, rect: Rect2D) -> Vec2 {
+        random_distributions::point_in_rect(&mut self.rng, rect)
+    }
+
+    /// Rolls dice, returning the total.
+    ///
+    /// # Arguments
+    ///
+    /// * `dice` - a [`DiceRoll`] or [`DiceType`](crate::DiceType) describing the dice to roll.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use my_library::{DiceType, RandomNumberGenerator};
+    /// let mut rng = RandomNumberGenerator::new();
+    /// let damage = rng.roll(DiceType::new(2, 8, 1));
+    /// assert!((3..=17).contains(&damage));
+    /// ```
+    pub fn roll(&mut self, dice: impl Into<DiceRoll>) -> i32 {
+        dice.into().roll_with(&mut self.rng)
+    }
+
+    /// Parses a dice expression (see [`DiceRoll`] for the supported
+    /// notation) and rolls it, returning the total.
+    ///
+    /// # Arguments
+    ///
+    /// * `notation` - the dice expression to roll, e.g. `"3d6+2"`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use my_library::RandomNumberGenerator;
+    /// let mut rng = RandomNumberGenerator::new();
+    /// let stat = rng.roll_str("4d6kh3").unwrap();
+    /// assert!((3..=18).contains(&stat));
+    /// assert!(rng.roll_str("4d").is_err());
+    /// ```
+    pub fn roll_str(&mut self, notation: &str) -> Result<i32, DiceParseError> {
+        dice::roll_str(&mut self.rng, notation)
+    }
+
+    /// Rolls on a weighted [`RandomTable`], returning the selected item
+    /// (or `None` if a "nothing" entry was rolled, or the table is empty).
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - the table to roll on.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use my_library::{RandomNumberGenerator, RandomTable};
+    /// let mut rng = RandomNumberGenerator::new();
+    /// let table = RandomTable::new().add("Sword", 1).add("Shield", 3);
+    /// let item = rng.roll_table(&table).unwrap();
+    /// assert!(*item == "Sword" || *item == "Shield");
+    /// ```
+    pub fn roll_table<'a, T>(&mut self, table: &'a RandomTable<T>) -> Option<&'a T> {
+        table.roll_with(&mut self.rng)
+    }
+
+    /// Shuffles a slice into a random order, in place.
+    ///
+    /// # Arguments
+    ///
+    /// * `items` - the slice to shuffle.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use my_library::RandomNumberGenerator;
+    /// let mut rng = RandomNumberGenerator::new();
+    /// let mut deck = vec![1, 2, 3, 4, 5];
+    /// rng.shuffle(&mut deck);
+    /// ```
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        items.shuffle(&mut self.rng);
+    }
+
+    /// Picks a random element from a slice, or `None` if it is empty.
+    ///
+    /// # Arguments
+    ///
+    /// * `items` - the slice to pick from.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use my_library::RandomNumberGenerator;
+    /// let mut rng = RandomNumberGenerator::new();
+    /// let greeting = rng.choose(&["Hello", "Howdy", "Hi"]).unwrap();
+    /// ```
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> Option<&'a T> {
+        items.choose(&mut self.rng)
+    }
+
+    /// Picks a random element from a slice, with the chance of each being
+    /// picked proportional to its weight. Returns `None` if the slice is
+    /// empty, or the weights are invalid (negative, or all zero).
+    ///
+    /// # Arguments
+    ///
+    /// * `items` - the slice to pick from.
+    /// * `weight` - returns the weight of an element.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use my_library::RandomNumberGenerator;
+    /// let mut rng = RandomNumberGenerator::new();
+    /// let spawns = [("Goblin", 10.0), ("Orc", 3.0), ("Dragon", 0.1)];
+    /// let (monster, _) = rng.choose_weighted(&spawns, |(_, weight)| *weight).unwrap();
+    /// ```
+    pub fn choose_weighted<'a, T>(
+        &mut self,
+        items: &'a [T],
+        weight: impl Fn(&T) -> f32,
+    ) -> Option<&'a T> {
+        random_distributions::choose_weighted(&mut self.rng, items, weight)
+    }
+
+    /// Picks `amount` distinct elements from a slice, in random order. If
+    /// the slice holds fewer than `amount` elements, all of them are
+    /// returned.
+    ///
+    /// # Arguments
+    ///
+    /// * `items` - the slice to pick from.
+    /// * `amount` - how many elements to pick.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use my_library::RandomNumberGenerator;
+    /// let mut rng = RandomNumberGenerator::new();
+    /// let hand = rng.choose_multiple(&[1, 2, 3, 4, 5, 6], 3);
+    /// assert_eq!(hand.len(), 3);
+    /// ```
+    pub fn choose_multiple<'a, T>(&mut self, items: &'a [T], amount: usize) -> Vec<&'a T> {
+        random_distributions::choose_multiple(&mut self.rng, items, amount)
+    }
+
+    /// Picks `amount` distinct indices from `0..length`, in random order.
+    /// If `amount` is larger than `length`, every index is returned.
+    ///
+    /// # Arguments
+    ///
+    /// * `length` - the number of indices to pick from.
+    /// * `amount` - how many indices to pick.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use my_library::RandomNumberGenerator;
+    /// let mut rng = RandomNumberGenerator::new();
+    /// let indices = rng.sample_indices(100, 10);
+    /// assert_eq!(indices.len(), 10);
+    /// assert!(indices.iter().all(|i| *i < 100));
+    /// ```
+    pub fn sample_indices(&mut self, length: usize, amount: usize) -> Vec<usize> {
+        random_distributions::sample_indices(&mut self.rng, length, amount)
     }
 }
 
-/// `Random` is a Bevy plugin that inserts a `RandomNumberGenerator`
-/// resource into your application.
+/// `RngSnapshot` captures the complete state of a
+/// [`RandomNumberGenerator`]. Restoring a snapshot resumes the exact same
+/// stream of random numbers, which is useful for save games and replays.
 ///
-/// Once you add the plugin (with `App::new().add_plugin(Random)`),
-/// you can access a random number generator in systems with
-/// `rng: ResMut<RandomNumberGenerator>`.
-pub struct RandomPlugin;
+/// With the `serde` feature enabled, snapshots can be serialized.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct RngSnapshot {
+    state: RngCore,
+}
 
-impl bevy::prelude::Plugin for RandomPlugin {
-    fn build(&self, app: &mut bevy::prelude::App) {
-        app.insert_resource(RandomNumberGenerator::new());
+impl Default for RandomNumberGenerator {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -162,4 +541,108 @@ mod tests {
             assert!(n >= -5000.0 && n < 5000.0);
         }
     }
+
+    #[test]
+    fn test_roll_str() {
+        let mut rng = RandomNumberGenerator::seeded(42);
+        for _ in 0..1000 {
+            let n = rng.roll_str("3d6+2").unwrap();
+            assert!((5..=20).contains(&n));
+        }
+        assert!(rng.roll_str("3d").is_err());
+    }
+
+    #[test]
+    fn test_roll_seeded_reproducibility() {
+        let mut rng1 = RandomNumberGenerator::seeded(42);
+        let mut rng2 = RandomNumberGenerator::seeded(42);
+        let dice = DiceRoll::parse("4d6!dl1").unwrap();
+        for _ in 0..1000 {
+            assert_eq!(rng1.roll(dice), rng2.roll(dice));
+        }
+    }
+
+    #[test]
+    fn test_snapshot_restore() {
+        let mut rng = RandomNumberGenerator::seeded(42);
+        let _: u64 = rng.next();
+        let snapshot = rng.snapshot();
+        let first: Vec<u32> = (0..100).map(|_| rng.next()).collect();
+        rng.restore(&snapshot);
+        let second: Vec<u32> = (0..100).map(|_| rng.next()).collect();
+        assert_eq!(first, second);
+
+        let mut copy = RandomNumberGenerator::from_snapshot(&snapshot);
+        let third: Vec<u32> = (0..100).map(|_| copy.next()).collect();
+        assert_eq!(first, third);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_snapshot_serde() {
+        let mut rng = RandomNumberGenerator::seeded(42);
+        let json = serde_json::to_string(&rng.snapshot()).unwrap();
+        let expected: u32 = rng.next();
+        let snapshot: RngSnapshot = serde_json::from_str(&json).unwrap();
+        let mut restored = RandomNumberGenerator::from_snapshot(&snapshot);
+        assert_eq!(expected, restored.next::<u32>());
+    }
+
+    #[test]
+    fn test_fork() {
+        let mut rng = RandomNumberGenerator::seeded(42);
+        let before = rng.snapshot();
+        let mut a = rng.fork("worldgen");
+        let mut b = rng.fork("worldgen");
+        assert_eq!(before, rng.snapshot());
+        for _ in 0..100 {
+            assert_eq!(a.next::<u32>(), b.next::<u32>());
+        }
+        assert_ne!(
+            rng.fork("worldgen").snapshot(),
+            rng.fork("combat").snapshot()
+        );
+        let _: u32 = rng.next();
+        assert_ne!(rng.fork("worldgen").snapshot(), a.snapshot());
+    }
+
+    #[test]
+    fn test_collections() {
+        let mut rng = RandomNumberGenerator::seeded(42);
+        let mut items: Vec<u32> = (0..20).collect();
+        rng.shuffle(&mut items);
+        let mut sorted = items.clone();
+        sorted.sort();
+        assert_eq!(sorted, (0..20).collect::<Vec<u32>>());
+
+        assert!(items.contains(rng.choose(&items).unwrap()));
+        assert_eq!(rng.choose::<u32>(&[]), None);
+
+        let weighted = [(1, 0.0), (2, 1.0)];
+        for _ in 0..100 {
+            assert_eq!(rng.choose_weighted(&weighted, |(_, w)| *w), Some(&(2, 1.0)));
+        }
+        assert_eq!(rng.choose_weighted(&weighted[..1], |(_, w)| *w), None);
+
+        let mut chosen = rng.choose_multiple(&items, 5);
+        chosen.sort();
+        chosen.dedup();
+        assert_eq!(chosen.len(), 5);
+        assert_eq!(rng.choose_multiple(&items, 50).len(), 20);
+
+        let mut indices = rng.sample_indices(10, 20);
+        indices.sort();
+        assert_eq!(indices, (0..10).collect::<Vec<usize>>());
+    }
+
+    #[test]
+    fn test_roll_table() {
+        let mut rng = RandomNumberGenerator::seeded(42);
+        let table = RandomTable::new().add(1, 1).add(2, 1).add_nothing(1);
+        for _ in 0..1000 {
+            if let Some(n) = rng.roll_table(&table) {
+                assert!(*n == 1 || *n == 2);
+            }
+        }
+    }
 }
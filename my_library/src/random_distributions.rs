@@ -0,0 +1,129 @@
+use crate::Rect2D;
+use bevy::math::Vec2;
+use rand::{
+    Rng,
+    seq::{SliceRandom, index},
+};
+use rand_distr::{Distribution, Exp, Normal, Poisson, Triangular};
+use std::f32::consts::TAU;
+
+pub(crate) fn normal<R: Rng + ?Sized>(rng: &mut R, mean: f32, std_dev: f32) -> f32 {
+    Normal::new(mean, std_dev)
+        .expect("standard deviation must be finite and non-negative")
+        .sample(rng)
+}
+
+pub(crate) fn triangular<R: Rng + ?Sized>(rng: &mut R, min: f32, max: f32, mode: f32) -> f32 {
+    Triangular::new(min, max, mode)
+        .expect("triangular distribution requires min <= mode <= max")
+        .sample(rng)
+}
+
+pub(crate) fn exponential<R: Rng + ?Sized>(rng: &mut R, lambda: f32) -> f32 {
+    Exp::new(lambda)
+        .expect("exponential rate must be non-negative")
+        .sample(rng)
+}
+
+pub(crate) fn poisson<R: Rng + ?Sized>(rng: &mut R, lambda: f32) -> u32 {
+    let sample: f32 = Poisson::new(lambda)
+        .expect("Poisson rate must be positive and finite")
+        .sample(rng);
+    sample as u32
+}
+
+pub(crate) fn unit_vec2<R: Rng + ?Sized>(rng: &mut R) -> Vec2 {
+    Vec2::from_angle(rng.gen_range(0.0..TAU))
+}
+
+pub(crate) fn point_in_circle<R: Rng + ?Sized>(rng: &mut R, center: Vec2, radius: f32) -> Vec2 {
+    // Taking the square root keeps points evenly spread over the area,
+    // rather than bunched up near the center.
+    let distance = radius * rng.r#gen::<f32>().sqrt();
+    center + unit_vec2(rng) * distance
+}
+
+pub(crate) fn point_in_rect<R: Rng + ?Sized>(rng: &mut R, rect: Rect2D) -> Vec2 {
+    let (min, max) = (rect.min(), rect.max());
+    Vec2::new(
+        min.x + rng.r#gen::<f32>() * (max.x - min.x),
+        min.y + rng.r#gen::<f32>() * (max.y - min.y),
+    )
+}
+
+pub(crate) fn choose_weighted<'a, R: Rng + ?Sized, T>(
+    rng: &mut R,
+    items: &'a [T],
+    weight: impl Fn(&T) -> f32,
+) -> Option<&'a T> {
+    items.choose_weighted(rng, weight).ok()
+}
+
+pub(crate) fn choose_multiple<'a, R: Rng + ?Sized, T>(
+    rng: &mut R,
+    items: &'a [T],
+    amount: usize,
+) -> Vec<&'a T> {
+    // `choose_multiple` doesn't randomize the order of what it picks.
+    let mut chosen: Vec<&T> = items.choose_multiple(rng, amount).collect();
+    chosen.shuffle(rng);
+    chosen
+}
+
+pub(crate) fn sample_indices<R: Rng + ?Sized>(
+    rng: &mut R,
+    length: usize,
+    amount: usize,
+) -> Vec<usize> {
+    index::sample(rng, length, amount.min(length)).into_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    fn mean(samples: &[f32]) -> f32 {
+        samples.iter().sum::<f32>() / samples.len() as f32
+    }
+
+    #[test]
+    fn test_distribution_means() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(1);
+        let normal: Vec<f32> = (0..10_000).map(|_| normal(&mut rng, 5.0, 2.0)).collect();
+        assert!((mean(&normal) - 5.0).abs() < 0.1);
+
+        let triangular: Vec<f32> = (0..10_000)
+            .map(|_| triangular(&mut rng, 0.0, 3.0, 0.0))
+            .collect();
+        assert!(triangular.iter().all(|n| (0.0..=3.0).contains(n)));
+        assert!((mean(&triangular) - 1.0).abs() < 0.1);
+
+        let exponential: Vec<f32> = (0..10_000).map(|_| exponential(&mut rng, 2.0)).collect();
+        assert!(exponential.iter().all(|n| *n >= 0.0));
+        assert!((mean(&exponential) - 0.5).abs() < 0.05);
+
+        let poisson: Vec<f32> = (0..10_000).map(|_| poisson(&mut rng, 4.0) as f32).collect();
+        assert!((mean(&poisson) - 4.0).abs() < 0.1);
+    }
+
+    #[test]
+    fn test_geometry() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(1);
+        let center = Vec2::new(10.0, -5.0);
+        let rect = Rect2D::new(Vec2::new(-1.0, 2.0), Vec2::new(3.0, 4.0));
+        for _ in 0..1000 {
+            assert!((unit_vec2(&mut rng).length() - 1.0).abs() < 1e-5);
+            assert!(point_in_circle(&mut rng, center, 3.0).distance(center) <= 3.0 + 1e-5);
+            let point = point_in_rect(&mut rng, rect);
+            assert!((-1.0..=3.0).contains(&point.x) && (2.0..=4.0).contains(&point.y));
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_invalid_triangular() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(1);
+        triangular(&mut rng, 0.0, 1.0, 2.0);
+    }
+}
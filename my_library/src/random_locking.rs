@@ -1,20 +1,39 @@
-use rand::{Rng, SeedableRng, distributions::uniform::SampleRange};
+use bevy::math::Vec2;
+use rand::{Rng, SeedableRng, distributions::uniform::SampleRange, seq::SliceRandom};
 use std::sync::Mutex;
 
+use crate::{
+    DiceParseError, DiceRoll, RandomTable, Rect2D, dice, random_distributions,
+    random_streams::derive_seed,
+};
+
 pub use rand;
 
+// `ChaCha12Rng` is the algorithm behind `rand`'s `StdRng`; it is named
+// directly because, unlike `StdRng`, its state can be serialized.
 #[cfg(all(not(feature = "pcg"), not(feature = "xorshift")))]
-type RngCore = rand::prelude::StdRng;
+type RngCore = rand_chacha::ChaCha12Rng;
 
-#[cfg(feature = "pcg")]
+// `pcg` is a default feature, so an explicitly enabled `xorshift` wins.
+#[cfg(all(feature = "pcg", not(feature = "xorshift")))]
 type RngCore = rand_pcg::Pcg64Mcg;
 
 #[cfg(feature = "xorshift")]
 type RngCore = rand_xorshift::XorShiftRng;
 
+/// `RngSnapshot` captures the complete state of a [`RandomNumberGenerator`].
+/// Restoring a snapshot resumes the exact same stream of random numbers, which
+/// is useful for save games and replays.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct RngSnapshot {
+    state: RngCore,
+}
+
 #[derive(bevy::prelude::Resource)]
 pub struct RandomNumberGenerator {
-    pub rng: Mutex<RngCore>,
+    pub(crate) rng: Mutex<RngCore>,
 }
 
 impl RandomNumberGenerator {
@@ -30,6 +49,37 @@ impl RandomNumberGenerator {
         }
     }
 
+    /// Creates a new `RandomNumberGenerator` from a previously captured
+    /// [`RngSnapshot`].
+    pub fn from_snapshot(snapshot: &RngSnapshot) -> Self {
+        Self {
+            rng: Mutex::new(snapshot.state.clone()),
+        }
+    }
+
+    /// Captures the current state of the generator.
+    pub fn snapshot(&self) -> RngSnapshot {
+        let lock = self.rng.lock().unwrap();
+        RngSnapshot {
+            state: lock.clone(),
+        }
+    }
+
+    /// Restores the generator to a previously captured state. Subsequent
+    /// requests produce the same results they did after the snapshot was taken.
+    pub fn restore(&self, snapshot: &RngSnapshot) {
+        let mut lock = self.rng.lock().unwrap();
+        *lock = snapshot.state.clone();
+    }
+
+    /// Derives a named child generator from the current state. The child is
+    /// independent of its parent, and forking does not advance the parent: the
+    /// same state and name always produce the same child.
+    pub fn fork(&self, name: &str) -> Self {
+        let base: u64 = self.rng.lock().unwrap().clone().r#gen();
+        Self::seeded(derive_seed(base, name, 0))
+    }
+
     pub fn range<T>(&self, range: impl SampleRange<T>) -> T
     where
         T: rand::distributions::uniform::SampleUniform + PartialOrd,
@@ -45,6 +95,111 @@ impl RandomNumberGenerator {
         let mut lock = self.rng.lock().unwrap();
         lock.r#gen()
     }
+
+    /// Draws from a normal (Gaussian) distribution: results cluster around the
+    /// mean, and about 68% of them fall within one standard deviation of it.
+    pub fn normal(&self, mean: f32, std_dev: f32) -> f32 {
+        let mut lock = self.rng.lock().unwrap();
+        random_distributions::normal(&mut *lock, mean, std_dev)
+    }
+
+    /// Draws from a triangular distribution: results fall between `min` and
+    /// `max`, and are most likely to be close to `mode`.
+    pub fn triangular(&self, min: f32, max: f32, mode: f32) -> f32 {
+        let mut lock = self.rng.lock().unwrap();
+        random_distributions::triangular(&mut *lock, min, max, mode)
+    }
+
+    /// Draws from an exponential distribution: the time between events that
+    /// happen, on average, `lambda` times per unit of time.
+    pub fn exponential(&self, lambda: f32) -> f32 {
+        let mut lock = self.rng.lock().unwrap();
+        random_distributions::exponential(&mut *lock, lambda)
+    }
+
+    /// Draws from a Poisson distribution: how many events occur in a unit of
+    /// time, if they happen on average `lambda` times per unit of time.
+    pub fn poisson(&self, lambda: f32) -> u32 {
+        let mut lock = self.rng.lock().unwrap();
+        random_distributions::poisson(&mut *lock, lambda)
+    }
+
+    /// Generates a vector of length one, pointing in a random direction.
+    pub fn random_unit_vec2(&self) -> Vec2 {
+        let mut lock = self.rng.lock().unwrap();
+        random_distributions::unit_vec2(&mut *lock)
+    }
+
+    /// Generates a point inside a circle. Points are evenly distributed over
+    /// the circle's area.
+    pub fn random_point_in_circle(&self, center: Vec2, radius: f32) -> Vec2 {
+        let mut lock = self.rng.lock().unwrap();
+        random_distributions::point_in_circle(&mut *lock, center, radius)
+    }
+
+    /// Generates a point inside a rectangle.
+    pub fn random_point_in_rect(&self, rect: Rect2D) -> Vec2 {
+        let mut lock = self.rng.lock().unwrap();
+        random_distributions::point_in_rect(&mut *lock, rect)
+    }
+
+    /// Rolls dice, returning the total.
+    pub fn roll(&self, dice: impl Into<DiceRoll>) -> i32 {
+        let mut lock = self.rng.lock().unwrap();
+        dice.into().roll_with(&mut *lock)
+    }
+
+    /// Parses a dice expression (see [`DiceRoll`] for the supported notation)
+    /// and rolls it, returning the total.
+    pub fn roll_str(&self, notation: &str) -> Result<i32, DiceParseError> {
+        let mut lock = self.rng.lock().unwrap();
+        dice::roll_str(&mut *lock, notation)
+    }
+
+    /// Rolls on a weighted [`RandomTable`], returning the selected item (or
+    /// `None` if a "nothing" entry was rolled, or the table is empty).
+    pub fn roll_table<'a, T>(&self, table: &'a RandomTable<T>) -> Option<&'a T> {
+        let mut lock = self.rng.lock().unwrap();
+        table.roll_with(&mut *lock)
+    }
+
+    /// Shuffles a slice into a random order, in place.
+    pub fn shuffle<T>(&self, items: &mut [T]) {
+        let mut lock = self.rng.lock().unwrap();
+        items.shuffle(&mut *lock);
+    }
+
+    /// Picks a random element from a slice, or `None` if it is empty.
+    pub fn choose<'a, T>(&self, items: &'a [T]) -> Option<&'a T> {
+        let mut lock = self.rng.lock().unwrap();
+        items.choose(&mut *lock)
+    }
+
+    /// Picks a random element from a slice, with the chance of each being
+    /// picked proportional to its weight. Returns `None` if the slice is empty,
+    /// or the weights are invalid (negative, or all zero).
+    pub fn choose_weighted<'a, T>(
+        &self,
+        items: &'a [T],
+        weight: impl Fn(&T) -> f32,
+    ) -> Option<&'a T> {
+        let mut lock = self.rng.lock().unwrap();
+        random_distributions::choose_weighted(&mut *lock, items, weight)
+    }
+
+    /// Picks `amount` distinct elements from a slice, in random order. If the
+    /// slice holds fewer than `amount` elements, all of them are returned.
+    pub fn choose_multiple<'a, T>(&self, items: &'a [T], amount: usize) -> Vec<&'a T> {
+        let mut lock = self.rng.lock().unwrap();
+        random_distributions::choose_multiple(&mut *lock, items, amount)
+    }
+
+    /// Picks `amount` distinct indices from `0..length`, in random order. If
+    /// `amount` is larger than `length`, every index is returned.
+    pub fn sample_indices(&self, length: usize, amount: usize) -> Vec<usize> {
+        let mut lock = self.rng.lock().unwrap();
+        random_distributions::sample_indices(&mut *lock, length, amount)
+    }
 }
 
 impl Default for RandomNumberGenerator {
@@ -53,14 +208,6 @@ impl Default for RandomNumberGenerator {
     }
 }
 
-pub struct RandomPlugin;
-
-impl bevy::prelude::Plugin for RandomPlugin {
-    fn build(&self, app: &mut bevy::prelude::App) {
-        app.insert_resource(RandomNumberGenerator::new());
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -104,4 +251,108 @@ mod tests {
             assert!(n >= -5000.0 && n < 5000.0);
         }
     }
+
+    #[test]
+    fn test_roll_str() {
+        let rng = RandomNumberGenerator::seeded(42);
+        for _ in 0..1000 {
+            let n = rng.roll_str("3d6+2").unwrap();
+            assert!((5..=20).contains(&n));
+        }
+        assert!(rng.roll_str("3d").is_err());
+    }
+
+    #[test]
+    fn test_roll_seeded_reproducibility() {
+        let rng1 = RandomNumberGenerator::seeded(42);
+        let rng2 = RandomNumberGenerator::seeded(42);
+        let dice = DiceRoll::parse("4d6!dl1").unwrap();
+        for _ in 0..1000 {
+            assert_eq!(rng1.roll(dice), rng2.roll(dice));
+        }
+    }
+
+    #[test]
+    fn test_snapshot_restore() {
+        let rng = RandomNumberGenerator::seeded(42);
+        let _: u64 = rng.next();
+        let snapshot = rng.snapshot();
+        let first: Vec<u32> = (0..100).map(|_| rng.next()).collect();
+        rng.restore(&snapshot);
+        let second: Vec<u32> = (0..100).map(|_| rng.next()).collect();
+        assert_eq!(first, second);
+
+        let copy = RandomNumberGenerator::from_snapshot(&snapshot);
+        let third: Vec<u32> = (0..100).map(|_| copy.next()).collect();
+        assert_eq!(first, third);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_snapshot_serde() {
+        let rng = RandomNumberGenerator::seeded(42);
+        let json = serde_json::to_string(&rng.snapshot()).unwrap();
+        let expected: u32 = rng.next();
+        let snapshot: RngSnapshot = serde_json::from_str(&json).unwrap();
+        let restored = RandomNumberGenerator::from_snapshot(&snapshot);
+        assert_eq!(expected, restored.next::<u32>());
+    }
+
+    #[test]
+    fn test_fork() {
+        let rng = RandomNumberGenerator::seeded(42);
+        let before = rng.snapshot();
+        let a = rng.fork("worldgen");
+        let b = rng.fork("worldgen");
+        assert_eq!(before, rng.snapshot());
+        for _ in 0..100 {
+            assert_eq!(a.next::<u32>(), b.next::<u32>());
+        }
+        assert_ne!(
+            rng.fork("worldgen").snapshot(),
+            rng.fork("combat").snapshot()
+        );
+        let _: u32 = rng.next();
+        assert_ne!(rng.fork("worldgen").snapshot(), a.snapshot());
+    }
+
+    #[test]
+    fn test_collections() {
+        let rng = RandomNumberGenerator::seeded(42);
+        let mut items: Vec<u32> = (0..20).collect();
+        rng.shuffle(&mut items);
+        let mut sorted = items.clone();
+        sorted.sort();
+        assert_eq!(sorted, (0..20).collect::<Vec<u32>>());
+
+        assert!(items.contains(rng.choose(&items).unwrap()));
+        assert_eq!(rng.choose::<u32>(&[]), None);
+
+        let weighted = [(1, 0.0), (2, 1.0)];
+        for _ in 0..100 {
+            assert_eq!(rng.choose_weighted(&weighted, |(_, w)| *w), Some(&(2, 1.0)));
+        }
+        assert_eq!(rng.choose_weighted(&weighted[..1], |(_, w)| *w), None);
+
+        let mut chosen = rng.choose_multiple(&items, 5);
+        chosen.sort();
+        chosen.dedup();
+        assert_eq!(chosen.len(), 5);
+        assert_eq!(rng.choose_multiple(&items, 50).len(), 20);
+
+        let mut indices = rng.sample_indices(10, 20);
+        indices.sort();
+        assert_eq!(indices, (0..10).collect::<Vec<usize>>());
+    }
+
+    #[test]
+    fn test_roll_table() {
+        let rng = RandomNumberGenerator::seeded(42);
+        let table = RandomTable::new().add(1, 1).add(2, 1).add_nothing(1);
+        for _ in 0..1000 {
+            if let Some(n) = rng.roll_table(&table) {
+                assert!(*n == 1 || *n == 2);
+            }
+        }
+    }
 }
@@ -0,0 +1,186 @@
+use crate::{RandomNumberGenerator, RandomStreams};
+use bevy::prelude::*;
+
+/// `RandomPlugin` is a Bevy plugin that inserts a `RandomNumberGenerator`
+/// resource into your application, along with a [`RandomStreams`] resource
+/// derived from the same seed.
+///
+/// Once you add the plugin (with `App::new().add_plugins(RandomPlugin)`),
+/// you can access a random number generator in systems with
+/// `rng: ResMut<RandomNumberGenerator>`.
+///
+/// By default, the seed is chosen at random. The chosen seed is always
+/// logged when the app starts, so a run can be reproduced exactly by passing the
+/// logged seed back in. The seed is picked from (in order of precedence):
+///
+/// 1. The command-line argument, if enabled with
+///    [`with_seed_from_args`](RandomPlugin::with_seed_from_args).
+/// 2. The environment variable, if enabled with
+///    [`with_seed_from_env`](RandomPlugin::with_seed_from_env).
+/// 3. The fixed seed provided to [`seeded`](RandomPlugin::seeded).
+/// 4. A random seed.
+///
+/// ## Example
+///
+/// ```no_run
+/// use bevy::prelude::*;
+/// use my_library::RandomPlugin;
+/// App::new()
+///     .add_plugins(
+///         RandomPlugin::new()
+///             .with_seed_from_env("GAME_SEED")
+///             .with_seed_from_args("--seed"),
+///     )
+///     .run();
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct RandomPlugin {
+    seed: Option<u64>,
+    env_var: Option<String>,
+    arg: Option<String>,
+}
+
+/// A `RandomPlugin` that picks a random seed, so that
+/// `add_plugins(RandomPlugin)` keeps working.
+#[allow(non_upper_case_globals)]
+pub const RandomPlugin: RandomPlugin = RandomPlugin::new();
+
+impl RandomPlugin {
+    /// Creates a `RandomPlugin` that picks a random seed.
+    pub const fn new() -> Self {
+        Self {
+            seed: None,
+            env_var: None,
+            arg: None,
+        }
+    }
+
+    /// Creates a `RandomPlugin` that always uses the same seed.
+    ///
+    /// # Arguments
+    ///
+    /// * `seed` - the seed to use.
+    pub fn seeded(seed: u64) -> Self {
+        Self {
+            seed: Some(seed),
+            ..default()
+        }
+    }
+
+    /// Reads the seed from an environment variable, if it is set.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - the name of the environment variable, e.g. `"GAME_SEED"`.
+    pub fn with_seed_from_env(mut self, name: &str) -> Self {
+        self.env_var = Some(name.to_string());
+        self
+    }
+
+    /// Reads the seed from a command-line argument, if it is present. Both
+    /// `--seed 1234` and `--seed=1234` are accepted.
+    ///
+    /// # Arguments
+    ///
+    /// * `flag` - the argument that precedes the seed, e.g. `"--seed"`.
+    pub fn with_seed_from_args(mut self, flag: &str) -> Self {
+        self.arg = Some(flag.to_string());
+        self
+    }
+
+    fn resolve_seed(
+        &self,
+        env: impl Fn(&str) -> Option<String>,
+        args: &[String],
+    ) -> (u64, &'static str) {
+        if let Some(flag) = &self.arg
+            && let Some(value) = find_arg(args, flag)
+        {
+            match value.parse() {
+                Ok(seed) => return (seed, "command line"),
+                Err(_) => warn!("Ignoring invalid random seed {value:?} passed to {flag}"),
+            }
+        }
+        if let Some(name) = &self.env_var
+            && let Some(value) = env(name)
+        {
+            match value.trim().parse() {
+                Ok(seed) => return (seed, "environment"),
+                Err(_) => warn!("Ignoring invalid random seed {value:?} in ${name}"),
+            }
+        }
+        match self.seed {
+            Some(seed) => (seed, "fixed"),
+            None => (rand::random(), "random"),
+        }
+    }
+}
+
+fn find_arg<'a>(args: &'a [String], flag: &str) -> Option<&'a str> {
+    args.iter().enumerate().find_map(|(i, arg)| {
+        if arg == flag {
+            args.get(i + 1).map(String::as_str)
+        } else {
+            arg.strip_prefix(flag)?.strip_prefix('=')
+        }
+    })
+}
+
+impl Plugin for RandomPlugin {
+    fn build(&self, app: &mut App) {
+        let args: Vec<String> = std::env::args().collect();
+        let (seed, source) = self.resolve_seed(|name| std::env::var(name).ok(), &args);
+        // Logging from `build` would be lost if the plugin were added before
+        // the `LogPlugin`.
+        app.insert_resource(RandomNumberGenerator::seeded(seed))
+            .insert_resource(RandomStreams::new(seed))
+            .add_systems(Startup, move || info!("Random seed ({source}): {seed}"));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn test_seed_precedence() {
+        let plugin = RandomPlugin::seeded(1)
+            .with_seed_from_env("SEED")
+            .with_seed_from_args("--seed");
+        let env = |_: &str| Some("2".to_string());
+        let no_env = |_: &str| None;
+
+        assert_eq!(plugin.resolve_seed(no_env, &[]).0, 1);
+        assert_eq!(plugin.resolve_seed(env, &[]).0, 2);
+        assert_eq!(
+            plugin.resolve_seed(env, &args(&["game", "--seed", "3"])).0,
+            3
+        );
+        assert_eq!(plugin.resolve_seed(env, &args(&["game", "--seed=4"])).0, 4);
+        assert_eq!(
+            plugin.resolve_seed(env, &args(&["game", "--seed", "x"])).0,
+            2
+        );
+        assert_eq!(RandomPlugin::seeded(1).resolve_seed(env, &[]).0, 1);
+    }
+
+    #[test]
+    fn test_unit_plugin_picks_a_random_seed() {
+        let mut app = App::new();
+        app.add_plugins(RandomPlugin);
+        assert!(app.world().contains_resource::<RandomNumberGenerator>());
+    }
+
+    #[test]
+    fn test_plugin_inserts_seeded_resources() {
+        let mut app = App::new();
+        app.add_plugins(RandomPlugin::seeded(42));
+        let rng = app.world().resource::<RandomNumberGenerator>();
+        assert_eq!(rng.snapshot(), RandomNumberGenerator::seeded(42).snapshot());
+        assert_eq!(app.world().resource::<RandomStreams>().seed(), 42);
+    }
+}
@@ -0,0 +1,229 @@
+use crate::{
+    DiceParseError, DiceRoll, RandomNumberGenerator, RandomTable, Rect2D, dice,
+    random_distributions,
+};
+use bevy::{ecs::system::SystemParam, prelude::*};
+use rand::{
+    Rng,
+    distributions::{
+        Distribution, Standard,
+        uniform::{SampleRange, SampleUniform},
+    },
+    seq::SliceRandom,
+};
+
+/// `RandomSource` is implemented by everything that can generate random
+/// numbers: [`RandomNumberGenerator`] (with or without the `locking`
+/// feature), and the [`Random`] system parameter.
+///
+/// `RandomNumberGenerator` takes `&mut self` without the `locking`
+/// feature, and `&self` with it. `RandomSource` always takes `&mut self`,
+/// so code written against it compiles either way.
+///
+/// Implementors only need to provide [`with_rng`](RandomSource::with_rng);
+/// every other method is built on top of it, and behaves like the
+/// `RandomNumberGenerator` method with the same name.
+///
+/// ## Example
+///
+/// ```
+/// use my_library::{RandomNumberGenerator, RandomSource};
+///
+/// fn roll_stat(rng: &mut impl RandomSource) -> i32 {
+///     rng.roll_str("4d6kh3").unwrap()
+/// }
+///
+/// let mut rng = RandomNumberGenerator::new();
+/// assert!((3..=18).contains(&roll_stat(&mut rng)));
+/// ```
+pub trait RandomSource {
+    /// Runs `f` with exclusive access to the underlying generator.
+    ///
+    /// # Arguments
+    ///
+    /// * `f` - the function to run.
+    fn with_rng<T>(&mut self, f: impl FnOnce(&mut dyn rand::RngCore) -> T) -> T;
+
+    /// See [`RandomNumberGenerator::range`].
+    fn range<T>(&mut self, range: impl SampleRange<T>) -> T
+    where
+        T: SampleUniform + PartialOrd,
+    {
+        self.with_rng(|rng| rng.gen_range(range))
+    }
+
+    /// See [`RandomNumberGenerator::next`].
+    fn next<T>(&mut self) -> T
+    where
+        Standard: Distribution<T>,
+    {
+        self.with_rng(|rng| rng.r#gen())
+    }
+
+    /// See [`RandomNumberGenerator::roll`].
+    fn roll(&mut self, dice: impl Into<DiceRoll>) -> i32 {
+        let dice = dice.into();
+        self.with_rng(|rng| dice.roll_with(rng))
+    }
+
+    /// See [`RandomNumberGenerator::roll_str`].
+    fn roll_str(&mut self, notation: &str) -> Result<i32, DiceParseError> {
+        self.with_rng(|rng| dice::roll_str(rng, notation))
+    }
+
+    /// See [`RandomNumberGenerator::roll_table`].
+    fn roll_table<'a, T>(&mut self, table: &'a RandomTable<T>) -> Option<&'a T> {
+        self.with_rng(|rng| table.roll_with(rng))
+    }
+
+    /// See [`RandomNumberGenerator::normal`].
+    fn normal(&mut self, mean: f32, std_dev: f32) -> f32 {
+        self.with_rng(|rng| random_distributions::normal(rng, mean, std_dev))
+    }
+
+    /// See [`RandomNumberGenerator::triangular`].
+    fn triangular(&mut self, min: f32, max: f32, mode: f32) -> f32 {
+        self.with_rng(|rng| random_distributions::triangular(rng, min, max, mode))
+    }
+
+    /// See [`RandomNumberGenerator::exponential`].
+    fn exponential(&mut self, lambda: f32) -> f32 {
+        self.with_rng(|rng| random_distributions::exponential(rng, lambda))
+    }
+
+    /// See [`RandomNumberGenerator::poisson`].
+    fn poisson(&mut self, lambda: f32) -> u32 {
+        self.with_rng(|rng| random_distributions::poisson(rng, lambda))
+    }
+
+    /// See [`RandomNumberGenerator::random_unit_vec2`].
+    fn random_unit_vec2(&mut self) -> Vec2 {
+        self.with_rng(|rng| random_distributions::unit_vec2(rng))
+    }
+
+    /// See [`RandomNumberGenerator::random_point_in_circle`].
+    fn random_point_in_circle(&mut self, center: Vec2, radius: f32) -> Vec2 {
+        self.with_rng(|rng| random_distributions::point_in_circle(rng, center, radius))
+    }
+
+    /// See [`RandomNumberGenerator::random_point_in_rect`].
+    fn random_point_in_rect(&mut self, rect: Rect2D) -> Vec2 {
+        self.with_rng(|rng| random_distributions::point_in_rect(rng, rect))
+    }
+
+    /// See [`RandomNumberGenerator::shuffle`].
+    fn shuffle<T>(&mut self, items: &mut [T]) {
+        self.with_rng(|rng| items.shuffle(rng))
+    }
+
+    /// See [`RandomNumberGenerator::choose`].
+    fn choose<'a, T>(&mut self, items: &'a [T]) -> Option<&'a T> {
+        self.with_rng(|rng| items.choose(rng))
+    }
+
+    /// See [`RandomNumberGenerator::choose_weighted`].
+    fn choose_weighted<'a, T>(
+        &mut self,
+        items: &'a [T],
+        weight: impl Fn(&T) -> f32,
+    ) -> Option<&'a T> {
+        self.with_rng(|rng| random_distributions::choose_weighted(rng, items, weight))
+    }
+
+    /// See [`RandomNumberGenerator::choose_multiple`].
+    fn choose_multiple<'a, T>(&mut self, items: &'a [T], amount: usize) -> Vec<&'a T> {
+        self.with_rng(|rng| random_distributions::choose_multiple(rng, items, amount))
+    }
+
+    /// See [`RandomNumberGenerator::sample_indices`].
+    fn sample_indices(&mut self, length: usize, amount: usize) -> Vec<usize> {
+        self.with_rng(|rng| random_distributions::sample_indices(rng, length, amount))
+    }
+}
+
+/// `Random` is a system parameter providing access to the
+/// [`RandomNumberGenerator`] resource through [`RandomSource`]. It
+/// borrows the resource mutably without the `locking` feature and
+/// immutably with it, so systems using it compile either way.
+///
+/// ## Example
+///
+/// ```
+/// use my_library::{Random, RandomSource};
+///
+/// fn spawn_enemy(mut rng: Random) {
+///     let hit_points = rng.roll_str("2d8+2").unwrap();
+/// }
+/// ```
+#[derive(SystemParam)]
+pub struct Random<'w> {
+    #[cfg(not(feature = "locking"))]
+    rng: ResMut<'w, RandomNumberGenerator>,
+    #[cfg(feature = "locking")]
+    rng: Res<'w, RandomNumberGenerator>,
+}
+
+impl RandomSource for RandomNumberGenerator {
+    #[cfg(not(feature = "locking"))]
+    fn with_rng<T>(&mut self, f: impl FnOnce(&mut dyn rand::RngCore) -> T) -> T {
+        f(&mut self.rng)
+    }
+
+    #[cfg(feature = "locking")]
+    fn with_rng<T>(&mut self, f: impl FnOnce(&mut dyn rand::RngCore) -> T) -> T {
+        f(&mut *self.rng.lock().unwrap())
+    }
+}
+
+impl RandomSource for Random<'_> {
+    #[cfg(not(feature = "locking"))]
+    fn with_rng<T>(&mut self, f: impl FnOnce(&mut dyn rand::RngCore) -> T) -> T {
+        self.rng.with_rng(f)
+    }
+
+    #[cfg(feature = "locking")]
+    fn with_rng<T>(&mut self, f: impl FnOnce(&mut dyn rand::RngCore) -> T) -> T {
+        f(&mut *self.rng.rng.lock().unwrap())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn draw(rng: &mut impl RandomSource) -> (u32, i32, Vec<usize>) {
+        (
+            rng.range(0..1000),
+            rng.roll_str("3d6").unwrap(),
+            rng.sample_indices(10, 3),
+        )
+    }
+
+    #[test]
+    fn test_generic_code_matches_inherent_methods() {
+        let mut generic = RandomNumberGenerator::seeded(42);
+        #[allow(unused_mut)]
+        let mut inherent = RandomNumberGenerator::seeded(42);
+        let expected = (
+            inherent.range(0..1000),
+            inherent.roll_str("3d6").unwrap(),
+            inherent.sample_indices(10, 3),
+        );
+        assert_eq!(draw(&mut generic), expected);
+    }
+
+    #[test]
+    fn test_system_param() {
+        #[derive(Resource)]
+        struct Drawn((u32, i32, Vec<usize>));
+
+        let mut app = App::new();
+        app.insert_resource(RandomNumberGenerator::seeded(42))
+            .add_systems(Update, |mut commands: Commands, mut rng: Random| {
+                commands.insert_resource(Drawn(draw(&mut rng)));
+            });
+        app.update();
+        let expected = draw(&mut RandomNumberGenerator::seeded(42));
+        assert_eq!(app.world().resource::<Drawn>().0, expected);
+    }
+}
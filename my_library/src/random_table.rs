@@ -0,0 +1,223 @@
+use rand::Rng;
+
+/// One possible outcome of a [`RandomTable`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum TableResult<T> {
+    /// Rolling this entry produces the item.
+    Item(T),
+    /// Rolling this entry rolls again on a nested table.
+    Table(RandomTable<T>),
+    /// Rolling this entry produces nothing at all.
+    Nothing,
+}
+
+/// A [`TableResult`] together with its relative weight.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TableEntry<T> {
+    /// How likely this entry is, relative to the other entries in the table.
+    /// Entries with a weight of zero are never picked.
+    pub weight: u32,
+    /// What rolling this entry produces.
+    pub result: TableResult<T>,
+}
+
+/// `RandomTable` picks from a list of weighted outcomes, in the style of a
+/// tabletop loot or encounter table. Entries may be items, nested tables
+/// (which are rolled in turn), or "nothing".
+///
+/// With the `serde` feature enabled, tables can be loaded from (and saved
+/// to) any format supported by `serde`, so designers can tune them in a
+/// data file.
+///
+/// ## Example
+///
+/// ```
+/// use my_library::{RandomNumberGenerator, RandomTable};
+/// let gems = RandomTable::new().add("Ruby", 1).add("Emerald", 1);
+/// let loot = RandomTable::new()
+///     .add("Gold", 10)
+///     .add_table(gems, 2)
+///     .add_nothing(5);
+///
+/// let mut rng = RandomNumberGenerator::new();
+/// if let Some(item) = rng.roll_table(&loot) {
+///     println!("You found: {item}");
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct RandomTable<T> {
+    entries: Vec<TableEntry<T>>,
+}
+
+impl<T> RandomTable<T> {
+    /// Creates an empty `RandomTable`.
+    pub fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+        }
+    }
+
+    /// Adds an item to the table.
+    ///
+    /// # Arguments
+    ///
+    /// * `item` - the item produced when this entry is rolled.
+    /// * `weight` - how likely this entry is, relative to the others.
+    pub fn add(self, item: T, weight: u32) -> Self {
+        self.add_entry(TableResult::Item(item), weight)
+    }
+
+    /// Adds a nested table. When this entry is rolled, the nested table is
+    /// rolled to decide the result.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - the table to roll on.
+    /// * `weight` - how likely this entry is, relative to the others.
+    pub fn add_table(self, table: RandomTable<T>, weight: u32) -> Self {
+        self.add_entry(TableResult::Table(table), weight)
+    }
+
+    /// Adds an entry that produces no result.
+    ///
+    /// # Arguments
+    ///
+    /// * `weight` - how likely this entry is, relative to the others.
+    pub fn add_nothing(self, weight: u32) -> Self {
+        self.add_entry(TableResult::Nothing, weight)
+    }
+
+    fn add_entry(mut self, result: TableResult<T>, weight: u32) -> Self {
+        self.entries.push(TableEntry { weight, result });
+        self
+    }
+
+    /// The entries in the table.
+    pub fn entries(&self) -> &[TableEntry<T>] {
+        &self.entries
+    }
+
+    /// The sum of the weights of every entry in the table. This is a `u64`
+    /// so that no table, however it was built or loaded, can overflow it.
+    pub fn total_weight(&self) -> u64 {
+        self.entries
+            .iter()
+            .map(|entry| u64::from(entry.weight))
+            .sum()
+    }
+
+    /// Returns `true` if the table can never produce anything.
+    pub fn is_empty(&self) -> bool {
+        self.total_weight() == 0
+    }
+
+    /// Rolls on the table with the provided generator, following nested
+    /// tables until an item or "nothing" is reached.
+    pub(crate) fn roll_with<R: Rng + ?Sized>(&self, rng: &mut R) -> Option<&T> {
+        let total = self.total_weight();
+        if total == 0 {
+            return None;
+        }
+
+        let mut roll = rng.gen_range(0..total);
+        for entry in self.entries.iter() {
+            let weight = u64::from(entry.weight);
+            if roll < weight {
+                return match &entry.result {
+                    TableResult::Item(item) => Some(item),
+                    TableResult::Table(table) => table.roll_with(rng),
+                    TableResult::Nothing => None,
+                };
+            }
+            roll -= weight;
+        }
+        None
+    }
+}
+
+impl<T> Default for RandomTable<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_empty_table() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(1);
+        let table: RandomTable<i32> = RandomTable::new().add(1, 0);
+        assert!(table.is_empty());
+        assert_eq!(table.roll_with(&mut rng), None);
+    }
+
+    #[test]
+    fn test_weights() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(1);
+        let table = RandomTable::new().add("common", 9).add("rare", 1);
+        let rare = (0..10_000)
+            .filter(|_| table.roll_with(&mut rng) == Some(&"rare"))
+            .count();
+        assert!((800..1200).contains(&rare));
+    }
+
+    #[test]
+    fn test_nested_and_nothing() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(1);
+        let inner = RandomTable::new().add(2, 1).add(3, 1);
+        let table = RandomTable::new()
+            .add(1, 1)
+            .add_table(inner, 1)
+            .add_nothing(1);
+        let mut seen = [false; 4];
+        for _ in 0..1000 {
+            match table.roll_with(&mut rng) {
+                Some(n) => seen[*n as usize] = true,
+                None => seen[0] = true,
+            }
+        }
+        assert!(seen.iter().all(|s| *s));
+    }
+
+    #[test]
+    fn test_huge_weights() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(1);
+        let table = RandomTable::new()
+            .add("common", u32::MAX)
+            .add("rare", u32::MAX)
+            .add_nothing(1);
+        assert_eq!(table.total_weight(), 2 * u64::from(u32::MAX) + 1);
+        let rare = (0..10_000)
+            .filter(|_| table.roll_with(&mut rng) == Some(&"rare"))
+            .count();
+        assert!((4500..5500).contains(&rare));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_deserialize() {
+        let json = r#"[
+            { "weight": 5, "result": { "item": "Gold" } },
+            { "weight": 1, "result": { "table": [
+                { "weight": 1, "result": { "item": "Ruby" } }
+            ] } },
+            { "weight": 2, "result": "nothing" }
+        ]"#;
+        let table: RandomTable<String> = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            table,
+            RandomTable::new()
+                .add("Gold".to_string(), 5)
+                .add_table(RandomTable::new().add("Ruby".to_string(), 1), 1)
+                .add_nothing(2)
+        );
+    }
+}
@@ -2,3 +2,4 @@
 cargo test
 cargo test --no-default-features --features xorshift
 cargo test --no-default-features --features pcg
+cargo test --features locking
@@ -1,10 +1,11 @@
 use bevy::prelude::*;
-use bevy_egui::{EguiContexts, EguiPlugin, egui};
+use bevy_egui::{EguiContexts, egui};
 use my_library::*;
 
 #[derive(Clone, Copy, PartialEq, Eq, Debug, Hash, Default, States)]
 enum GamePhase {
     #[default]
+    Loading,
     MainMenu,
     Start,
     Player,
@@ -16,7 +17,7 @@ enum GamePhase {
 #[derive(Component)]
 pub struct GameElement;
 
-fn main() {
+fn main() -> anyhow::Result<()> {
     let mut app = App::new();
 
     add_phase!(app, GamePhase, GamePhase::Start,
@@ -52,11 +53,15 @@ fn main() {
         GamePhase::Start,
         GamePhase::GameOver,
     ))
-    .add_plugins(EguiPlugin {
-        enable_multipass_for_primary_context: false,
-    })
     .add_plugins(RandomPlugin)
+    .add_plugins(
+        AssetManager::new()
+            .add_image("main_menu", "main_menu.png")?
+            .add_image("game_over", "game_over.png")?,
+    )
     .run();
+
+    Ok(())
 }
 
 #[derive(Resource)]
@@ -138,7 +143,7 @@ fn clear_die(hand_query: &Query<(Entity, &Sprite), With<HandDie>>, commands: &mu
 fn player(
     hand_query: Query<(Entity, &Sprite), With<HandDie>>,
     mut commands: Commands,
-    rng: Res<RandomNumberGenerator>,
+    mut rng: Random,
     assets: Res<GameAssets>,
     mut scores: ResMut<Scores>,
     mut state: ResMut<NextState<GamePhase>>,
@@ -179,7 +184,7 @@ fn cpu(
     hand_query: Query<(Entity, &Sprite), With<HandDie>>,
     mut state: ResMut<NextState<GamePhase>>,
     mut scores: ResMut<Scores>,
-    rng: Res<RandomNumberGenerator>,
+    mut rng: Random,
     mut commands: Commands,
     assets: Res<GameAssets>,
     mut timer: ResMut<HandTimer>,